@@ -0,0 +1,22 @@
+use std::path::Path;
+
+use configuration_scraper::configuration::Configuration;
+use feature_model::FeatureModel;
+use num_bigint::BigUint;
+
+/// Common interface for the backends answering analysis questions about feature models.
+pub trait Analyzer {
+    /// Sets the current model to apply the other operations to.
+    /// The model is given both as the path to its UVL file and as the model itself,
+    /// so every backend can pick the representation it works on.
+    fn set_model(&mut self, path: &Path, model: &FeatureModel) -> anyhow::Result<()>;
+
+    /// The number of configurations of the current model, ignoring cross-tree constraints.
+    fn estimated_number_of_configurations(&mut self) -> anyhow::Result<BigUint>;
+
+    /// The exact number of configurations of the current model.
+    fn configurations_number(&mut self) -> anyhow::Result<BigUint>;
+
+    /// Determines if the current model satisfies the configuration stored at the given path.
+    fn satisfiable_configuration(&mut self, path: &Path, configuration: &Configuration) -> anyhow::Result<bool>;
+}
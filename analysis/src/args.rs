@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 
+use crate::config::Backend;
+
 #[derive(clap::Parser)]
 pub struct Args {
     #[arg(short, long)]
@@ -24,4 +26,12 @@ pub struct Args {
     pub max_configs: Option<usize>,
     #[arg(long)]
     pub max_dependencies: Option<usize>,
+    #[arg(long, value_enum)]
+    pub backend: Option<Backend>,
+    /// Writes every feature model in the DIMACS format next to its UVL file.
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    pub write_dimacs: Option<bool>,
+    /// Keeps the cross-tree constraints of the FCA models which are implied by the rest of the model.
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    pub keep_redundant_constraints: Option<bool>,
 }
\ No newline at end of file
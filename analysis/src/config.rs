@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use clap::ValueEnum;
 use nameof::name_of;
 
 use crate::args::Args;
@@ -14,6 +15,18 @@ pub struct Config {
     pub min_configs: usize,
     pub max_configs: usize,
     pub max_dependencies: usize,
+    pub backend: Backend,
+    pub write_dimacs: bool,
+    pub keep_redundant_constraints: bool,
+}
+
+/// The backend used to analyze feature models.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// Analyze models with flamapy through analysis/src/flamapy_server.py.
+    Flamapy,
+    /// Analyze models in-process with feature_model::engine.
+    Native,
 }
 
 impl Default for Config {
@@ -27,7 +40,10 @@ impl Default for Config {
             max_features: 100, 
             min_configs: 100, 
             max_configs: 1000, 
-            max_dependencies: 1000
+            max_dependencies: 1000,
+            backend: Backend::Flamapy,
+            write_dimacs: false,
+            keep_redundant_constraints: false,
         }
     }
 }
@@ -59,6 +75,13 @@ pub fn config_from_args(args: Args) -> anyhow::Result<Config> {
     let path_map = |k: &str| toml_config.get(k)
         .and_then(|v| v.as_str().map(PathBuf::from));
 
+    let bool_map = |k: &str| toml_config.get(k)
+        .and_then(|v| v.as_bool());
+
+    let backend_map = |k: &str| toml_config.get(k)
+        .and_then(|v| v.as_str())
+        .and_then(|s| Backend::from_str(s, true).ok());
+
     config_replace!(config, args, str_map, connection_string);
     config_replace!(config, args, path_map, data);
     config_replace!(config, args, path_map, result);
@@ -68,6 +91,9 @@ pub fn config_from_args(args: Args) -> anyhow::Result<Config> {
     config_replace!(config, args, usize_map, min_configs);
     config_replace!(config, args, usize_map, max_configs);
     config_replace!(config, args, usize_map, max_dependencies);
+    config_replace!(config, args, backend_map, backend);
+    config_replace!(config, args, bool_map, write_dimacs);
+    config_replace!(config, args, bool_map, keep_redundant_constraints);
 
     Ok(config)
 }
\ No newline at end of file
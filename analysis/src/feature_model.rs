@@ -1,16 +1,17 @@
-use std::{fs::File, io::{BufWriter, Write}};
+use std::{fs::File, io::{BufWriter, Write}, path::Path};
 
 use anyhow::Context;
-use cargo_toml::crate_id::CrateId;
+use analysis::config::Config;
+use cargo_toml::{crate_id::CrateId, manifest::Manifest};
 use configuration_scraper::configuration::Configuration;
-use feature_model::{FeatureModel, uvl};
+use feature_model::{FeatureModel, dimacs, simplify::{self, Pass}, uvl};
 use fm_synthesizer_fca::{concept, synthesizer, tree_constraints};
 
 use crate::paths::Paths;
 
 /// Create a declared feature model for a crate with the given crate id and Cargo.toml content.
-pub fn create_declared(id: &CrateId, table: &toml::Table, paths: &Paths) -> anyhow::Result<FeatureModel> {
-    let feature_model = fm_synthesizer_flat::fm_from_cargo_toml(table)
+pub fn create_declared(id: &CrateId, manifest: &Manifest, paths: &Paths, config: &Config) -> anyhow::Result<FeatureModel> {
+    let feature_model = fm_synthesizer_flat::fm_from_manifest(manifest)
         .with_context(|| format!("Failed to create flat constraints for {id}"))?;
 
     let path = paths.declared_model.join(format!("{id}.uvl"));
@@ -20,12 +21,16 @@ pub fn create_declared(id: &CrateId, table: &toml::Table, paths: &Paths) -> anyh
         .with_context(|| format!("Failed to write flat feature model to {path:?}"))?;
     writer.flush()
         .with_context(|| format!("Failed to flush file {path:?}"))?;
+    if config.write_dimacs {
+        write_dimacs(&feature_model, &path.with_extension("dimacs"))?;
+    }
 
     Ok(feature_model)
 }
 
 /// Create an FCA feature model for a crate with the given crate id and set of configurations.
-pub fn create_fca<'a>(id: &CrateId, configurations: &[Configuration<'a>], paths: &Paths) -> anyhow::Result<FeatureModel> {
+/// Unless the configuration keeps them, redundant cross-tree constraints are removed, and their number is returned with the model.
+pub fn create_fca<'a>(id: &CrateId, configurations: &[Configuration<'a>], paths: &Paths, config: &Config) -> anyhow::Result<(FeatureModel, usize)> {
     let path = paths.fca_model.join(format!("{id}.uvl"));
     let file = File::create(&path)?;
     let train_configurations = &configurations[..configurations.len() / 10];
@@ -38,12 +43,34 @@ pub fn create_fca<'a>(id: &CrateId, configurations: &[Configuration<'a>], paths:
 
     let ac_poset = concept::ac_poset(train_configurations, &features, &id.name);
     let tree_constraints = tree_constraints::max_depth::find(&ac_poset);
-    let feature_model = synthesizer::fm_from_ac_poset(&ac_poset, &features, &tree_constraints);
+    let mut feature_model = synthesizer::fm_from_ac_poset(&ac_poset, &features, &tree_constraints);
+    let dropped_constraints = if config.keep_redundant_constraints {
+        0
+    } else {
+        simplify::simplify(&mut feature_model, &[Pass::DropRedundantConstraints])
+            .with_context(|| format!("Failed to remove redundant cross-tree constraints from the fca feature model of {id}"))?
+            .dropped_constraints.len()
+    };
     let mut writer = BufWriter::new(file);
     uvl::write(&mut writer, &feature_model)
         .with_context(|| format!("Failed to write fca feature model to {path:?}"))?;
     writer.flush()
         .with_context(|| format!("Failed to flush file {path:?}"))?;
+    if config.write_dimacs {
+        write_dimacs(&feature_model, &path.with_extension("dimacs"))?;
+    }
 
-    Ok(feature_model)
-}
\ No newline at end of file
+    Ok((feature_model, dropped_constraints))
+}
+
+/// Writes the feature model in the DIMACS format next to its UVL file,
+/// so it can be given to external SAT solvers and model counters.
+fn write_dimacs(feature_model: &FeatureModel, path: &Path) -> anyhow::Result<()> {
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create DIMACS file {path:?}"))?;
+    let mut writer = BufWriter::new(file);
+    dimacs::write(&mut writer, feature_model)
+        .with_context(|| format!("Failed to write DIMACS file {path:?}"))?;
+    writer.flush()
+        .with_context(|| format!("Failed to flush file {path:?}"))
+}
@@ -1,7 +1,13 @@
 use std::{io::{BufRead, BufReader, BufWriter, Write}, num::ParseFloatError, path::Path, process::{ChildStdin, ChildStdout, Command, Stdio}};
 
+use configuration_scraper::configuration::Configuration;
+use feature_model::FeatureModel;
+use num_bigint::BigUint;
+use num_traits::FromPrimitive;
 use which::which;
 
+use crate::analyzer::Analyzer;
+
 /// Provides an interface to communicate with flamapy using flamapy_server.py.
 pub struct Client {
     writer: BufWriter<ChildStdin>,
@@ -42,23 +48,33 @@ impl Client {
     }
 
     /// Sends a request for the estimated number of configurations of the current model.
-    pub fn estimated_number_of_configurations(&mut self) -> Result<f64, CommandError> {
+    pub fn estimated_number_of_configurations(&mut self) -> Result<BigUint, CommandError> {
         writeln!(self.writer, "estimated_number_of_configurations")?;
         self.writer.flush()?;
-        let mut output = String::new();
-        self.reader.read_line(&mut output)?;
-        output.trim().parse()
-            .map_err(|e| CommandError::ParseFloat(e, output))
+        self.read_count()
     }
 
     /// Sends a request for the configurations number of the current model.
-    pub fn configurations_number(&mut self) -> Result<f64, CommandError> {
+    pub fn configurations_number(&mut self) -> Result<BigUint, CommandError> {
         writeln!(self.writer, "configurations_number")?;
         self.writer.flush()?;
+        self.read_count()
+    }
+
+    /// Reads a number of configurations from the server.
+    /// Flamapy prints integers exactly, but might fall back to printing floats.
+    fn read_count(&mut self) -> Result<BigUint, CommandError> {
         let mut output = String::new();
         self.reader.read_line(&mut output)?;
-        output.trim().parse()
-            .map_err(|e| CommandError::ParseFloat(e, output))
+        let trimmed = output.trim();
+        if let Ok(count) = trimmed.parse() {
+            return Ok(count);
+        }
+
+        let float = trimmed.parse::<f64>()
+            .map_err(|e| CommandError::ParseFloat(e, output.clone()))?;
+        BigUint::from_f64(float)
+            .ok_or(CommandError::InvalidCount(output))
     }
 
     /// Sends a request for determining if the current model satisfies the given configuration.
@@ -75,6 +91,24 @@ impl Client {
     }
 }
 
+impl Analyzer for Client {
+    fn set_model(&mut self, path: &Path, _model: &FeatureModel) -> anyhow::Result<()> {
+        Ok(Client::set_model(self, path)?)
+    }
+
+    fn estimated_number_of_configurations(&mut self) -> anyhow::Result<BigUint> {
+        Ok(Client::estimated_number_of_configurations(self)?)
+    }
+
+    fn configurations_number(&mut self) -> anyhow::Result<BigUint> {
+        Ok(Client::configurations_number(self)?)
+    }
+
+    fn satisfiable_configuration(&mut self, path: &Path, _configuration: &Configuration) -> anyhow::Result<bool> {
+        Ok(Client::satisfiable_configuration(self, path)?)
+    }
+}
+
 /// Error that might happen when starting and connecting to the server.
 #[derive(Debug, thiserror::Error)]
 pub enum ConnectionError {
@@ -99,4 +133,6 @@ pub enum CommandError {
     ParseFloat(ParseFloatError, String),
     #[error("Unable to parse bool from flamapy output:\n\t{0}")]
     ParseBool(String),
+    #[error("Flamapy output is not a valid number of configurations:\n\t{0}")]
+    InvalidCount(String),
 }
\ No newline at end of file
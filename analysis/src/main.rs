@@ -1,22 +1,24 @@
+mod analyzer;
 mod flamapy_client;
+mod native_analyzer;
 mod paths;
 mod feature_model;
 mod retry;
 
-use std::{collections::BTreeSet, fs::File, io::{BufWriter, Write}, path::{Path, PathBuf}};
+use std::{collections::{BTreeSet, HashMap}, path::{Path, PathBuf}};
 
-use analysis::{args::Args, config::config_from_args, result::{configuration_stats::ConfigStats, feature_stats::FeatureStats, line_count::LineCountRow, model_stats::ModelStats, satisfiability::SatisfiabilityRow}};
+use analysis::{args::Args, config::{Backend, config_from_args}, result::{configuration_stats::ConfigStats, feature_properties::FeatureProperties, feature_stats::FeatureStats, line_count::LineCountRow, model_metrics::ModelMetrics, model_stats::ModelStats, rejected_configuration::RejectedConfigurationRow, satisfiability::SatisfiabilityRow}};
 use anyhow::Context;
-use cargo_toml::{crate_id::CrateId, feature_dependencies, implied_features};
+use cargo_toml::{crate_id::CrateId, feature_dependencies, implied_features, manifest::Manifest};
 use clap::Parser;
 use configuration_scraper::{configuration::Configuration, postgres};
 use crate_scraper::crate_entry::CrateEntry;
-use ::feature_model::FeatureModel;
+use ::feature_model::{FeatureModel, count, engine::Engine, explain::Explainer, metrics::Metrics};
 use itertools::Itertools;
 use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
 use tokei::{LanguageType, Languages};
 
-use crate::{paths::Paths, retry::retry};
+use crate::{analyzer::Analyzer, native_analyzer::NativeAnalyzer, paths::Paths, retry::retry};
 
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
@@ -27,17 +29,24 @@ fn main() -> anyhow::Result<()> {
 
     let mut postgres_client = postgres::Client::connect(&config.connection_string, postgres::NoTls)
         .with_context(|| "Failed to create postgres client")?;
-    let mut flamapy_client = flamapy_client::Client::new(&paths.flamapy_server)
-        .with_context(|| "Failed to create flamapy client")?;
+    let mut analyzer: Box<dyn Analyzer> = match config.backend {
+        Backend::Flamapy => Box::new(flamapy_client::Client::new(&paths.flamapy_server)
+            .with_context(|| "Failed to create flamapy client")?),
+        Backend::Native => Box::new(NativeAnalyzer::default()),
+    };
     let reqwest_client = cargo_toml::default_reqwest_client()
         .with_context(|| "Failed to create reqwest client")?;
 
     let mut feature_stats_writer = csv::Writer::from_path(paths.result.join("feature_stats.csv"))?;
     let mut flat_model_stats_writer = csv::Writer::from_path(paths.result.join("flat_model_stats.csv"))?;
     let mut fca_model_stats_writer = csv::Writer::from_path(paths.result.join("fca_model_stats.csv"))?;
+    let mut flat_model_metrics_writer = csv::Writer::from_path(paths.result.join("flat_model_metrics.csv"))?;
+    let mut fca_model_metrics_writer = csv::Writer::from_path(paths.result.join("fca_model_metrics.csv"))?;
     let mut config_stats_writer = csv::Writer::from_path(paths.result.join("configuration_stats.csv"))?;
     let mut satisfiability_writer = csv::Writer::from_path(paths.result.join("satisfiability.csv"))?;
     let mut line_count_writer = csv::Writer::from_path(paths.result.join("line_count.csv"))?;
+    let mut rejected_configurations_writer = csv::Writer::from_path(paths.result.join("rejected_configurations.csv"))?;
+    let mut feature_properties_writer = csv::Writer::from_path(paths.result.join("feature_properties.csv"))?;
 
     let crate_entries = get_or_scrape_crate_entries(&mut postgres_client, config.number_of_crates, &paths)?
         .into_iter()
@@ -53,11 +62,13 @@ fn main() -> anyhow::Result<()> {
 
         let line_count_row = get_line_count(&id, &tokei_config, &paths)?;
         let cargo_toml = get_cargo_toml(&id, &paths)?;
-        let dependency_graph = feature_dependencies::from_cargo_toml(&cargo_toml)
-            .with_context(|| format!("Failed to create dependency graph for {id}"))?;
-        let feature_count = dependency_graph.node_count();
+        let dependency_graph = feature_dependencies::from_manifest(&cargo_toml);
+        let feature_count = feature_dependencies::features(&dependency_graph).count();
         let feature_dependency_count = dependency_graph.edge_count();
-        let default_features = implied_features::from_dependency_graph(["default"].into_iter(), &dependency_graph);
+        let default_features = implied_features::from_dependency_graph(["default"].into_iter(), &dependency_graph)
+            .into_iter()
+            .map(|node| node.to_string())
+            .collect::<BTreeSet<_>>();
         let feature_stats = FeatureStats::new(id.clone(), feature_count, feature_dependency_count);
 
         if feature_count > config.max_features {
@@ -72,31 +83,52 @@ fn main() -> anyhow::Result<()> {
             continue;
         }
 
-        let flat_model = feature_model::create_declared(&id, &cargo_toml, &paths)?;
-        let fca_model = feature_model::create_fca(&id, &crate_configs, &paths)?;
+        let flat_model = feature_model::create_declared(&id, &cargo_toml, &paths, &config)?;
+        let (fca_model, dropped_constraints) = feature_model::create_fca(&id, &crate_configs, &paths, &config)?;
+        if dropped_constraints > 0 {
+            println!("Removed {dropped_constraints} redundant cross-tree constraints from the fca feature model");
+        }
         let flat_model_path = paths.declared_model.join(format!("{id_str}.uvl"));
         let fca_model_path = paths.fca_model.join(format!("{id_str}.uvl"));
-        let flat_model_stats = get_model_stats(&mut flamapy_client, &id, &flat_model_path, &flat_model)?;
-        let fca_model_stats = get_model_stats(&mut flamapy_client, &id, &fca_model_path, &fca_model)?;
-
-        let satisfied_test_configurations = number_of_satisfied_configurations(&mut flamapy_client, &id, crate_test_configs)?;
+        let flat_model_stats = get_model_stats(analyzer.as_mut(), &id, &flat_model_path, &flat_model)?;
+        let fca_model_stats = get_model_stats(analyzer.as_mut(), &id, &fca_model_path, &fca_model)?;
+        let flat_model_metrics = ModelMetrics::new(id.clone(), &Metrics::new(&flat_model));
+        let fca_model_metrics = ModelMetrics::new(id.clone(), &Metrics::new(&fca_model));
+        let flat_feature_properties = get_feature_properties(&id, "Declared", &flat_model);
+        let fca_feature_properties = get_feature_properties(&id, "FCA", &fca_model);
+
+        let rejected_test_configurations = rejected_configurations(analyzer.as_mut(), &id, crate_test_configs)?;
+        let satisfied_test_configurations = crate_test_configs.len() - rejected_test_configurations.len();
         let satisfiability = satisfied_test_configurations as f64 / crate_test_configs.len() as f64;
         let satisfiability_row = SatisfiabilityRow::new(id.clone(), satisfiability);
+        let rejected_configurations = explain_rejected_configurations(&id, &fca_model, &rejected_test_configurations);
 
         feature_stats_writer.serialize(feature_stats)?;
         flat_model_stats_writer.serialize(flat_model_stats)?;
         fca_model_stats_writer.serialize(fca_model_stats)?;
+        flat_model_metrics_writer.serialize(flat_model_metrics)?;
+        fca_model_metrics_writer.serialize(fca_model_metrics)?;
         config_stats_writer.serialize(config_stats)?;
         satisfiability_writer.serialize(satisfiability_row)?;
         line_count_writer.serialize(line_count_row)?;
+        for row in rejected_configurations {
+            rejected_configurations_writer.serialize(row)?;
+        }
+        for row in flat_feature_properties.into_iter().chain(fca_feature_properties) {
+            feature_properties_writer.serialize(row)?;
+        }
     }
 
     feature_stats_writer.flush()?;
     flat_model_stats_writer.flush()?;
     fca_model_stats_writer.flush()?;
+    flat_model_metrics_writer.flush()?;
+    fca_model_metrics_writer.flush()?;
     config_stats_writer.flush()?;
     satisfiability_writer.flush()?;
     line_count_writer.flush()?;
+    rejected_configurations_writer.flush()?;
+    feature_properties_writer.flush()?;
 
     Ok(())
 }
@@ -158,12 +190,11 @@ fn get_line_count(id: &CrateId, config: &tokei::Config, paths: &Paths) -> anyhow
     Ok(LineCountRow::new(id.clone(), line_count))
 }
 
-fn get_cargo_toml(id: &CrateId, paths: &Paths) -> anyhow::Result<toml::Table> {
+fn get_cargo_toml(id: &CrateId, paths: &Paths) -> anyhow::Result<Manifest> {
     let path = paths.crates
         .join(id.to_string())
         .join("Cargo.toml");
-    let content = std::fs::read_to_string(&path)?;
-    content.parse()
+    Manifest::read(&path)
         .with_context(|| format!("Failed to parse Cargo.toml for {id}"))
 }
 
@@ -226,7 +257,7 @@ fn read_configuration(path: &Path) -> anyhow::Result<Configuration<'static>> {
         .with_context(|| format!("Failed to parse configuration file at {path:?}"))
 }
 
-fn get_configuration_stats(id: &CrateId, configs: &[Configuration<'static>], default_features: &BTreeSet<&str>) -> ConfigStats {
+fn get_configuration_stats(id: &CrateId, configs: &[Configuration<'static>], default_features: &BTreeSet<String>) -> ConfigStats {
     let configuration_count = configs.len();
 
     let default_configuration_count = configs
@@ -241,29 +272,77 @@ fn get_configuration_stats(id: &CrateId, configs: &[Configuration<'static>], def
     ConfigStats::new(id.clone(), configuration_count, default_configuration_count, unique_configuration_count)
 }
 
-fn get_model_stats(client: &mut flamapy_client::Client, id: &CrateId, path: &Path, model: &FeatureModel) -> anyhow::Result<ModelStats> {
-    client.set_model(path)
+fn get_model_stats(analyzer: &mut dyn Analyzer, id: &CrateId, path: &Path, model: &FeatureModel) -> anyhow::Result<ModelStats> {
+    analyzer.set_model(path, model)
         .with_context(|| format!("Failed to set model to {path:?}"))?;
 
     let features = model.count_features();
     let cross_tree_constraints = model.cross_tree_constraints.len();
 
-    let config_estimation = client.estimated_number_of_configurations()
+    let config_estimation = analyzer.estimated_number_of_configurations()
         .with_context(|| format!("Failed to get estimated number of configurations for {path:?}"))?;
 
-    let config_exact = client.configurations_number()
+    let config_exact = analyzer.configurations_number()
         .with_context(|| format!("Failed to get configuration number for {path:?}"))?;
 
-    Ok(ModelStats::new(id.clone(), features, cross_tree_constraints, config_estimation, config_exact))
+    let config_estimation_log10 = count::log10(&config_estimation);
+    let config_exact_log10 = count::log10(&config_exact);
+
+    Ok(ModelStats::new(id.clone(), features, cross_tree_constraints, config_estimation, config_exact, config_estimation_log10, config_exact_log10))
 }
 
-fn number_of_satisfied_configurations(client: &mut flamapy_client::Client, id: &CrateId, configurations: &[Configuration<'static>]) -> anyhow::Result<usize> {
+/// Finds the core, dead and false-optional features of the model, and the atomic set of every feature.
+fn get_feature_properties(id: &CrateId, model_name: &str, model: &FeatureModel) -> Vec<FeatureProperties> {
+    let mut engine = Engine::new(model);
+    let core = engine.core_features().into_iter().map(str::to_owned).collect::<BTreeSet<_>>();
+    let dead = engine.dead_features().into_iter().map(str::to_owned).collect::<BTreeSet<_>>();
+    let false_optional = engine.false_optional_features(model).into_iter().map(str::to_owned).collect::<BTreeSet<_>>();
+    let atomic_sets = engine.atomic_sets().into_iter()
+        .enumerate()
+        .flat_map(|(i, set)| set.into_iter().map(move |feature| (feature.to_owned(), i)))
+        .collect::<HashMap<_, _>>();
+
+    engine.features().iter()
+        .map(|feature| FeatureProperties::new(
+            id.clone(),
+            model_name.to_owned(),
+            feature.clone(),
+            core.contains(feature),
+            dead.contains(feature),
+            false_optional.contains(feature),
+            atomic_sets[feature],
+        ))
+        .collect()
+}
+
+/// Returns the configurations which the analyzer finds unsatisfiable in its current model.
+fn rejected_configurations<'a>(
+    analyzer: &mut dyn Analyzer,
+    id: &CrateId,
+    configurations: &'a [Configuration<'static>],
+) -> anyhow::Result<Vec<&'a Configuration<'static>>> {
     configurations.iter()
-        .map(|config| {
+        .filter_map(|config| {
             let path = PathBuf::from(format!("data/configuration/{id}/{}@{}.csvconf", config.name, config.version));
-            client.satisfiable_configuration(&path)
-                .map(|b| b as usize)
+            analyzer.satisfiable_configuration(&path, config)
                 .with_context(|| format!("Failed to check for satisfiable configuration for {}@{} for {id}", config.name, config.version))
+                .map(|satisfiable| (!satisfiable).then_some(config))
+                .transpose()
+        })
+        .collect()
+}
+
+/// Explains the given rejected configurations with the relations of the feature model that reject them.
+fn explain_rejected_configurations(id: &CrateId, model: &FeatureModel, configurations: &[&Configuration<'static>]) -> Vec<RejectedConfigurationRow> {
+    let mut explainer = Explainer::new(model);
+    configurations.iter()
+        .filter_map(|config| {
+            let features = config.features.iter()
+                .map(|(feature, &enabled)| (feature.as_ref(), enabled));
+            let relations = explainer.explain(features)?;
+            let explanation = relations.iter().map(|r| r.to_string()).join("; ");
+            let configuration = CrateId::new(config.name.clone(), config.version.clone());
+            Some(RejectedConfigurationRow::new(id.clone(), configuration, relations.len(), explanation))
         })
-        .fold_ok(0, |acc, x| acc + x)
-}
\ No newline at end of file
+        .collect()
+}
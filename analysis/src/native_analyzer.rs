@@ -0,0 +1,49 @@
+use std::path::Path;
+
+use anyhow::Context;
+use configuration_scraper::configuration::Configuration;
+use feature_model::{FeatureModel, engine::Engine};
+use num_bigint::BigUint;
+
+use crate::analyzer::Analyzer;
+
+/// Analyzes feature models in-process by compiling them with [Engine].
+#[derive(Default)]
+pub struct NativeAnalyzer {
+    model: Option<NativeModel>,
+}
+
+struct NativeModel {
+    engine: Engine,
+    estimated_configurations: BigUint,
+}
+
+impl NativeAnalyzer {
+    fn model(&mut self) -> anyhow::Result<&mut NativeModel> {
+        self.model.as_mut()
+            .context("Model not assigned")
+    }
+}
+
+impl Analyzer for NativeAnalyzer {
+    fn set_model(&mut self, _path: &Path, model: &FeatureModel) -> anyhow::Result<()> {
+        let engine = Engine::new(model);
+        let estimated_configurations = model.root_feature.config_count().clone();
+        self.model = Some(NativeModel { engine, estimated_configurations });
+        Ok(())
+    }
+
+    fn estimated_number_of_configurations(&mut self) -> anyhow::Result<BigUint> {
+        Ok(self.model()?.estimated_configurations.clone())
+    }
+
+    fn configurations_number(&mut self) -> anyhow::Result<BigUint> {
+        Ok(self.model()?.engine.configurations_number())
+    }
+
+    fn satisfiable_configuration(&mut self, _path: &Path, configuration: &Configuration) -> anyhow::Result<bool> {
+        let features = configuration.features.iter()
+            .map(|(feature, &enabled)| (feature.as_ref(), enabled));
+        Ok(self.model()?.engine.satisfiable_configuration(features))
+    }
+}
@@ -5,6 +5,7 @@ pub mod cross_tree_constraints;
 pub mod feature_stats;
 pub mod default_configs;
 pub mod unique_configs;
+pub mod tree_depth;
 
 use std::{iter::successors, ops::Range, path::Path};
 
@@ -164,6 +165,19 @@ pub fn log_label_formatter(x: &f64) -> String {
     }
 }
 
+/// Formats the base 10 logarithm of a number as the number itself.
+pub fn exponent_label_formatter(x: &f64) -> String {
+    let exponent = x.floor();
+    let coefficient = 10_f64.powf(x - exponent);
+    let exponent_super_script = superscript(exponent as i32);
+
+    if (coefficient - 1.0).abs() < 0.01 {
+        format!("10{exponent_super_script}")
+    } else {
+        format!("{coefficient:.1} × 10{exponent_super_script}")
+    }
+}
+
 pub fn integer_formatter(x: &f64) -> String {
     format!("{x:.0}")
 }
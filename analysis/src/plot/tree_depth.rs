@@ -0,0 +1,27 @@
+use std::path::Path;
+
+use itertools::izip;
+use plotters::data::fitting_range;
+
+use crate::{plot::{default_chart, default_mesh, default_root, draw_points}, result::model_metrics::ModelMetrics};
+
+pub fn plot(declared: &[ModelMetrics], fca: &[ModelMetrics], path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let caption = "Tree depth (Declared & FCA)";
+    let x_desc = "Tree depth (Declared)";
+    let y_desc = "Tree depth (FCA)";
+
+    let points = izip!(declared, fca)
+        .map(|(d, f)| (d.depth as f64, f.depth as f64))
+        .collect::<Vec<_>>();
+
+    let x_range = fitting_range(points.iter().map(|p| &p.0));
+    let y_range = fitting_range(points.iter().map(|p| &p.1));
+
+    let root = default_root(path.as_ref(), 1000, 600)?;
+    let mut chart = default_chart(&root, caption, x_range, y_range)?;
+    default_mesh(&mut chart, x_desc, y_desc).draw()?;
+    draw_points(&mut chart, &points)?;
+    root.present()?;
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use std::path::Path;
 
-use analysis::{args::Args, config::config_from_args, plot::{cross_tree_constraints, declared_vs_fca, default_configs, feature_stats, features_and_dependencies, line_count_and_features, unique_configs}, result::{configuration_stats::ConfigStats, feature_stats::FeatureStats, line_count::LineCountRow, model_stats::ModelStats}};
+use analysis::{args::Args, config::config_from_args, plot::{cross_tree_constraints, declared_vs_fca, default_configs, feature_stats, features_and_dependencies, line_count_and_features, tree_depth, unique_configs}, result::{configuration_stats::ConfigStats, feature_stats::FeatureStats, line_count::LineCountRow, model_metrics::ModelMetrics, model_stats::ModelStats}};
 use clap::Parser;
 use serde::de::DeserializeOwned;
 
@@ -17,6 +17,8 @@ fn main() -> anyhow::Result<()> {
     let fca_stats = load::<ModelStats>(result.join("fca_model_stats.csv"))?;
     let line_count_rows = load::<LineCountRow>(result.join("line_count.csv"))?;
     let config_stats = load::<ConfigStats>(result.join("configuration_stats.csv"))?;
+    let declared_metrics = load::<ModelMetrics>(result.join("flat_model_metrics.csv"))?;
+    let fca_metrics = load::<ModelMetrics>(result.join("fca_model_metrics.csv"))?;
 
     feature_stats::plot(&feature_stats, plot.join("feature_stats.png"))?;
     features_and_dependencies::plot(&feature_stats, plot.join("features_and_dependencies.png"))?;
@@ -25,6 +27,7 @@ fn main() -> anyhow::Result<()> {
     line_count_and_features::plot(&line_count_rows, &feature_stats, plot.join("line_count_and_features.png"))?;
     default_configs::plot(&config_stats, plot.join("default_configs.png"))?;
     unique_configs::plot(&config_stats, plot.join("unique_configs.png"))?;
+    tree_depth::plot(&declared_metrics, &fca_metrics, plot.join("tree_depth.png"))?;
 
     Ok(())
 }
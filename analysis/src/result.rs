@@ -1,5 +1,8 @@
 pub mod feature_stats;
 pub mod model_stats;
+pub mod model_metrics;
+pub mod feature_properties;
 pub mod configuration_stats;
 pub mod satisfiability;
 pub mod line_count;
+pub mod rejected_configuration;
@@ -0,0 +1,21 @@
+use cargo_toml::crate_id::CrateId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, derive_new::new)]
+pub struct FeatureProperties {
+    #[serde(rename = "Crate")]
+    pub crate_id: CrateId,
+    #[serde(rename = "Model")]
+    pub model: String,
+    #[serde(rename = "Feature")]
+    pub feature: String,
+    #[serde(rename = "Core")]
+    pub core: bool,
+    #[serde(rename = "Dead")]
+    pub dead: bool,
+    #[serde(rename = "False optional")]
+    pub false_optional: bool,
+    /// The index of the feature's atomic set within the model.
+    #[serde(rename = "Atomic set")]
+    pub atomic_set: usize,
+}
@@ -0,0 +1,62 @@
+use cargo_toml::crate_id::CrateId;
+use feature_model::metrics::Metrics;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelMetrics {
+    #[serde(rename = "Crate")]
+    pub crate_id: CrateId,
+    #[serde(rename = "Features")]
+    pub features: usize,
+    #[serde(rename = "Leaves")]
+    pub leaves: usize,
+    #[serde(rename = "Abstract features")]
+    pub abstract_features: usize,
+    #[serde(rename = "Abstract ratio")]
+    pub abstract_ratio: f64,
+    #[serde(rename = "Depth")]
+    pub depth: usize,
+    #[serde(rename = "Branching factor")]
+    pub branching_factor: f64,
+    #[serde(rename = "Mandatory groups")]
+    pub mandatory_groups: usize,
+    #[serde(rename = "Optional groups")]
+    pub optional_groups: usize,
+    #[serde(rename = "Or groups")]
+    pub or_groups: usize,
+    #[serde(rename = "Alternative groups")]
+    pub alternative_groups: usize,
+    #[serde(rename = "Cardinality groups")]
+    pub cardinality_groups: usize,
+    #[serde(rename = "Cross-tree constraints")]
+    pub cross_tree_constraints: usize,
+    #[serde(rename = "Constraint variables")]
+    pub constraint_variables: usize,
+    #[serde(rename = "Cross-tree constraint ratio")]
+    pub cross_tree_constraint_ratio: f64,
+    #[serde(rename = "Cyclomatic complexity")]
+    pub cyclomatic_complexity: usize,
+}
+
+impl ModelMetrics {
+    pub fn new(crate_id: CrateId, metrics: &Metrics) -> Self {
+        Self {
+            crate_id,
+            features: metrics.features,
+            leaves: metrics.leaves,
+            abstract_features: metrics.abstract_features,
+            abstract_ratio: metrics.abstract_ratio,
+            depth: metrics.depth,
+            branching_factor: metrics.branching_factor,
+            mandatory_groups: metrics.mandatory_groups,
+            optional_groups: metrics.optional_groups,
+            or_groups: metrics.or_groups,
+            alternative_groups: metrics.alternative_groups,
+            cardinality_groups: metrics.cardinality_groups,
+            cross_tree_constraints: metrics.cross_tree_constraints,
+            constraint_variables: metrics.constraint_variables,
+            cross_tree_constraint_ratio: metrics.cross_tree_constraint_ratio,
+            cyclomatic_complexity: metrics.cyclomatic_complexity,
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use cargo_toml::crate_id::CrateId;
+use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, derive_new::new)]
@@ -9,8 +10,28 @@ pub struct ModelStats {
     pub features: usize,
     #[serde(rename = "Cross-tree constraints")]
     pub cross_tree_constraints: usize,
-    #[serde(rename = "Estimated configurations")]
-    pub config_estimation: f64,
-    #[serde(rename = "Exact configurations")]
-    pub config_exact: f64,
+    #[serde(rename = "Estimated configurations", with = "decimal")]
+    pub config_estimation: BigUint,
+    #[serde(rename = "Exact configurations", with = "decimal")]
+    pub config_exact: BigUint,
+    #[serde(rename = "Estimated configurations (log10)")]
+    pub config_estimation_log10: f64,
+    #[serde(rename = "Exact configurations (log10)")]
+    pub config_exact_log10: f64,
+}
+
+/// Stores configuration counts as decimal strings, so they can be arbitrarily large.
+mod decimal {
+    use num_bigint::BigUint;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(count: &BigUint, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(count)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigUint, D::Error> {
+        let content = String::deserialize(deserializer)?;
+        content.parse()
+            .map_err(serde::de::Error::custom)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,14 @@
+use cargo_toml::crate_id::CrateId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, derive_new::new)]
+pub struct RejectedConfigurationRow {
+    #[serde(rename = "Crate")]
+    pub crate_id: CrateId,
+    #[serde(rename = "Configuration")]
+    pub configuration: CrateId,
+    #[serde(rename = "Relations")]
+    pub relation_count: usize,
+    #[serde(rename = "Explanation")]
+    pub explanation: String,
+}
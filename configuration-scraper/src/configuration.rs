@@ -1,14 +1,67 @@
 use std::{borrow::Cow, collections::BTreeMap};
 
+use cargo_toml::csvconf;
 use itertools::Itertools;
 use semver::Version;
+use serde::{Deserialize, Serialize};
 
+/// The version of the JSON schema of configurations, which is increased whenever the schema changes incompatibly.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The configuration of a crate used by a dependent crate.
+///
+/// In JSON, a configuration is an object with the schema_version, the name and version of the crate,
+/// and an object mapping every feature to whether it is enabled.
+#[derive(Deserialize)]
+#[serde(try_from = "ConfigurationData")]
 pub struct Configuration<'a> {
     pub name: String,
     pub version: Version,
     pub features: BTreeMap<Cow<'a, str>, bool>,
 }
 
+#[derive(Serialize)]
+struct ConfigurationRef<'b, 'a> {
+    schema_version: u32,
+    name: &'b str,
+    version: &'b Version,
+    features: &'b BTreeMap<Cow<'a, str>, bool>,
+}
+
+#[derive(Deserialize)]
+struct ConfigurationData {
+    schema_version: u32,
+    name: String,
+    version: Version,
+    features: BTreeMap<String, bool>,
+}
+
+impl Serialize for Configuration<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ConfigurationRef {
+            schema_version: SCHEMA_VERSION,
+            name: &self.name,
+            version: &self.version,
+            features: &self.features,
+        }.serialize(serializer)
+    }
+}
+
+impl TryFrom<ConfigurationData> for Configuration<'_> {
+    type Error = String;
+
+    fn try_from(data: ConfigurationData) -> Result<Self, Self::Error> {
+        if data.schema_version != SCHEMA_VERSION {
+            return Err(format!("Unsupported schema version {}, expected {SCHEMA_VERSION}", data.schema_version));
+        }
+
+        let features = data.features.into_iter()
+            .map(|(feature, enabled)| (Cow::Owned(feature), enabled))
+            .collect();
+        Ok(Configuration::new(data.name, data.version, features))
+    }
+}
+
 impl<'a> Configuration<'a> {
     pub fn new(name: String, version: Version, features: BTreeMap<Cow<'a, str>, bool>) -> Self {
         Self { name, version, features }
@@ -19,25 +72,29 @@ impl<'a> Configuration<'a> {
     }
 
     pub fn from_csv(name: String, version: Version, content: &'a str) -> Option<Configuration<'a>> {
-        let features = content.lines()
-            .map(|l| l.split_once(','))
-            .collect::<Option<Vec<_>>>()?
+        let features = csvconf::parse(content)?
             .into_iter()
-            .map(|(l, r)| (Cow::Borrowed(l.trim_matches('"')), r == "True"))
+            .map(|(feature, enabled)| (Cow::Borrowed(feature), enabled))
             .collect::<BTreeMap<_, _>>();
         Some(Configuration::new(name, version, features))
     }
 
     pub fn from_csv_owned(name: String, version: Version, content: &str) -> Option<Configuration<'static>> {
-        let features = content.lines()
-            .map(|l| l.split_once(','))
-            .collect::<Option<Vec<_>>>()?
+        let features = csvconf::parse(content)?
             .into_iter()
-            .map(|(l, r)| (Cow::Owned(l.trim_matches('"').to_string()), r == "True"))
+            .map(|(feature, enabled)| (Cow::Owned(feature.to_string()), enabled))
             .collect::<BTreeMap<_, _>>();
         Some(Configuration::new(name, version, features))
     }
 
+    pub fn from_json(content: &str) -> serde_json::Result<Configuration<'static>> {
+        serde_json::from_str(content)
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
     pub fn to_csv(&self) -> String {
         self.features.iter()
             .map(|(feature, &enabled)| format!("\"{feature}\",{}", if enabled { "True" } else { "False" }))
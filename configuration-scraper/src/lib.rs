@@ -17,7 +17,8 @@ pub fn scrape(
     client: &mut postgres::Client, 
     limit: usize
 ) -> Result<Vec<Configuration<'static>>, Error> {
-    let features = feature_dependencies.nodes()
+    let features = feature_dependencies::features(feature_dependencies)
+        .map(str::to_string)
         .collect::<Vec<_>>();
 
     let query = include_str!("query.sql");
@@ -35,7 +36,7 @@ pub fn scrape(
 fn row_to_config(
     row: &Row, 
     crate_version: &Version,
-    features: &[&str],
+    features: &[String],
     feature_dependencies: &feature_dependencies::Graph,
 ) -> Option<Configuration<'static>> {
     let dependent_name: String = row.get("dependent_crate");
@@ -62,7 +63,7 @@ fn row_to_config(
         .map(|f| Cow::Owned(f.to_string()))
         .collect::<BTreeSet<Cow<str>>>();
     let features = features.iter()
-        .map(|&s| s.to_owned())
+        .cloned()
         .map(Cow::Owned)
         .map(|s| {
             let is_enabled = enabled_features.contains(&s);
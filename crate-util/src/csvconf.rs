@@ -0,0 +1,11 @@
+use std::collections::BTreeMap;
+
+/// Parses a configuration in the .csvconf format, with one `"feature",True` or `"feature",False` line per feature.
+/// The feature names are borrowed from the content.
+/// Returns None if a line isn't of the form `"feature",value`.
+pub fn parse(content: &str) -> Option<BTreeMap<&str, bool>> {
+    content.lines()
+        .map(|l| l.split_once(','))
+        .map(|line| line.map(|(l, r)| (l.trim_matches('"'), r == "True")))
+        .collect()
+}
@@ -1,135 +1,157 @@
+use std::{collections::HashSet, fmt::Display};
+
 use itertools::Itertools;
-use petgraph::prelude::DiGraphMap;
+use petgraph::{Direction, prelude::DiGraphMap};
+
+use crate::manifest::Manifest;
+
+pub type Graph<'a> = DiGraphMap<Node<'a>, DependencyKind>;
 
-use crate::toml_util::{get_table, Error, Result};
+/// A node of the feature dependency graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Node<'a> {
+    /// A feature of the crate, including the implicit feature of an optional dependency, which stands for the dependency.
+    Feature(&'a str),
+    /// An optional dependency which is referenced with 'dep:', so it has no implicit feature.
+    Dependency(&'a str),
+    /// A feature of a dependency, like 'chrono/serde', given as the dependency and its feature.
+    DependencyFeature(&'a str, &'a str),
+}
 
-pub type Graph<'a> = DiGraphMap<&'a str, ()>;
+/// How a feature depends on a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyKind {
+    /// Enabling the feature always enables the node.
+    Strong,
+    /// Enabling the feature enables the feature of a dependency only if the dependency is enabled by something else,
+    /// like 'chrono?/serde'.
+    Weak,
+}
 
-/// Create a map between features and their dependencies from a toml table.
-pub fn from_cargo_toml(root: &toml::Table) -> Result<Graph<'_>> {
+/// Create a map between features and their dependencies from a manifest.
+///
+/// Every optional dependency has an implicit feature of the same name, which stands for the dependency,
+/// unless the dependency is referenced with 'dep:' somewhere in the feature table.
+/// Then the dependency is a [Node::Dependency] of its own.
+pub fn from_manifest(manifest: &Manifest) -> Graph<'_> {
     let mut feature_dependencies = Graph::new();
 
-    let feature_table = get_table(root, "features")?;
-    let dependency_tables = get_dependency_tables(root);
-    let features = scan_features(feature_table, &dependency_tables);
+    let optional = manifest.all_dependencies()
+        .filter(|(_, dependency)| dependency.optional)
+        .map(|(name, _)| name)
+        .unique()
+        .collect::<Vec<_>>();
+    let namespaced = namespaced_dependencies(manifest);
+    let features = scan_features(manifest, &optional, &namespaced);
     
     for feature in features {
-        feature_dependencies.add_node(feature);
+        feature_dependencies.add_node(Node::Feature(feature));
     }
 
-    explicit_feature_dependencies(feature_table, &mut feature_dependencies)?;
+    optional.iter()
+        .filter(|d| namespaced.contains(*d))
+        .for_each(|d| { feature_dependencies.add_node(Node::Dependency(d)); });
 
-    dependency_tables.into_iter()
-        .flat_map(optional_dependencies)
-        .filter(|d| !feature_dependencies.contains_node(d))
-        .collect::<Vec<_>>().into_iter()
-        .for_each(|d| { feature_dependencies.add_node(d); });
+    explicit_feature_dependencies(manifest, &optional, &mut feature_dependencies);
 
-    Ok(feature_dependencies)
+    feature_dependencies
 }
 
-fn scan_features<'a>(feature_table: &'a toml::Table, dependency_tables: &[&'a toml::Table]) -> impl Iterator<Item = &'a str> {
-    let explicit_features = feature_table.keys()
+fn scan_features<'a>(manifest: &'a Manifest, optional: &[&'a str], namespaced: &HashSet<&str>) -> impl Iterator<Item = &'a str> {
+    let explicit_features = manifest.features.keys()
         .map(|k| k.as_str());
-    let implicit_features = dependency_tables.iter()
-        .cloned()
-        .flat_map(optional_dependencies);
+    let implicit_features = optional.iter()
+        .copied()
+        .filter(|d| !namespaced.contains(d));
     explicit_features.chain(implicit_features)
         .chain(std::iter::once("default"))
         .unique()
 }
 
+/// Find all dependencies which are referenced with 'dep:' in the feature table.
+fn namespaced_dependencies(manifest: &Manifest) -> HashSet<&str> {
+    manifest.features.values()
+        .flatten()
+        .filter_map(|s| s.get_ref().strip_prefix("dep:"))
+        .collect()
+}
+
 /// Find all features and their dependencies that are explicitly listed in the feature table.
-fn explicit_feature_dependencies<'a>(table: &'a toml::Table, graph: &mut Graph<'a>) -> Result<()> {
-    for (key, value) in table {
-        let feature = key.as_str();
-        let dependencies = value.as_array()
-            .ok_or(Error::UnexpectedType(key.to_string(), "array"))?
-            .iter()
-            .enumerate()
-            .map(|(i, d)| d.as_str()
-                .map(trim_feature)
-                .ok_or(Error::UnexpectedType(format!("{}[{}]", key, i), "str"))
-            )
-            .filter_ok(|d| !d.ends_with('?'))
-            // Example: io-uring depends on dep:io-uring in tokio.
-            // After trimming, they are equal, which would lead to an incorrect
-            // dependency graph.
-            .filter_ok(|&dependency| dependency != feature)
-            .collect::<Result<Vec<&str>>>()?;
-
-        for dependency in dependencies {
-            if graph.contains_node(dependency) {
-                graph.add_edge(feature, dependency, ());
+///
+/// A feature of a dependency, like 'chrono/serde', becomes a node which depends on the optional dependency, if there is one,
+/// since enabling the feature of the dependency also enables the dependency.
+/// A weak reference, like 'chrono?/serde', becomes a [DependencyKind::Weak] edge to the same node.
+fn explicit_feature_dependencies<'a>(manifest: &'a Manifest, optional: &[&str], graph: &mut Graph<'a>) {
+    for (key, value) in &manifest.features {
+        let feature = Node::Feature(key.as_str());
+        let dependencies = value.iter()
+            .map(|d| referenced_node(d.get_ref()));
+
+        for (dependency, mut kind) in dependencies {
+            if let Node::DependencyFeature(optional_dependency, _) = dependency {
+                graph.add_node(dependency);
+                if graph.contains_node(Node::Dependency(optional_dependency)) {
+                    graph.add_edge(dependency, Node::Dependency(optional_dependency), DependencyKind::Strong);
+                } else if optional.contains(&optional_dependency) {
+                    graph.add_edge(dependency, Node::Feature(optional_dependency), DependencyKind::Strong);
+                } else {
+                    // A dependency which isn't optional is always enabled.
+                    kind = DependencyKind::Strong;
+                }
+            }
+
+            // A strong reference to the same node wins over a weak one.
+            if graph.contains_node(dependency) && graph.edge_weight(feature, dependency) != Some(&DependencyKind::Strong) {
+                graph.add_edge(feature, dependency, kind);
             }
         }
     }
-
-    Ok(())
 }
 
-/// Find all dependencies marked as optional
-fn optional_dependencies(table: &toml::Table) -> impl Iterator<Item = &str> {
-    table.iter()
-        .filter(|&(_k, v)| dependency_is_optional(v))
-        .map(|(k, _v)| k.as_str())
+/// Lists the features of the crate, without the dependencies and the features of dependencies,
+/// which can't be enabled by a dependent on their own.
+pub fn features<'a, 'g>(graph: &'g Graph<'a>) -> impl Iterator<Item = &'a str> + 'g {
+    graph.nodes()
+        .filter_map(|node| match node {
+            Node::Feature(feature) => Some(feature),
+            Node::Dependency(_) | Node::DependencyFeature(_, _) => None,
+        })
 }
 
-// Determine if a dependency's toml value indicates it is optional.
-fn dependency_is_optional(dependency: &toml::Value) -> bool {
-    dependency.as_table()
-        .and_then(|t| t.get("optional"))
-        .and_then(|v| v.as_bool())
-        .unwrap_or(false)
-}
+/// Finds the node which an entry in the list of a feature refers to, and how the feature depends on it.
+///
+/// 'dep:<dependency>' refers to the [Node::Dependency], '<dependency>/<feature>' refers to
+/// the feature of the dependency, and the weak reference '<dependency>?/<feature>' refers to the same node.
+pub fn referenced_node(s: &str) -> (Node<'_>, DependencyKind) {
+    if let Some(dependency) = s.strip_prefix("dep:") {
+        return (Node::Dependency(dependency), DependencyKind::Strong);
+    }
 
-/// Find all tables with dependencies or dev-dependencies
-pub fn get_dependency_tables(root: &toml::Table) -> Vec<&toml::Table> {
-    let default_table = get_table(root, "dependencies").ok();
-    let dev_table = get_table(root, "dev-dependencies").ok();
-    let target_table = root.get("target")
-        .and_then(|v| v.as_table());
-
-    let target_dependency_tables = if let Some(target) = target_table {
-        target.values()
-            .filter_map(|v| v.as_table())
-            .filter_map(|t| get_table(t, "dependencies").ok())
-            .collect::<Vec<_>>()
-    } else {
-        vec![]
-    };
+    match s.split_once('/') {
+        Some((dependency, feature)) => match dependency.strip_suffix('?') {
+            Some(dependency) => (Node::DependencyFeature(dependency, feature), DependencyKind::Weak),
+            None => (Node::DependencyFeature(dependency, feature), DependencyKind::Strong),
+        },
+        None => (Node::Feature(s), DependencyKind::Strong),
+    }
+}
 
-    let target_dev_dependency_tables = if let Some(target) = target_table {
-        target.values()
-            .filter_map(|v| v.as_table())
-            .filter_map(|t| get_table(t, "dev-dependencies").ok())
-            .collect::<Vec<_>>()
-    } else {
-        vec![]
+/// Finds the node of the optional dependency which is enabled by a feature of the dependency,
+/// or None if the dependency isn't optional.
+pub fn dependency_node<'a>(graph: &Graph<'a>, dependency_feature: Node<'a>) -> Option<Node<'a>> {
+    let Node::DependencyFeature(dependency, _) = dependency_feature else {
+        return None;
     };
+    graph.neighbors_directed(dependency_feature, Direction::Outgoing)
+        .find(|&n| n == Node::Dependency(dependency) || n == Node::Feature(dependency))
+}
 
-    let mut tables = vec![];
-    
-    if let Some(table) = default_table {
-        tables.push(table);
-    }
-    if let Some(table) = dev_table {
-        tables.push(table);
+impl Display for Node<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Node::Feature(feature) => write!(f, "{feature}"),
+            Node::Dependency(dependency) => write!(f, "dep:{dependency}"),
+            Node::DependencyFeature(dependency, feature) => write!(f, "{dependency}/{feature}"),
+        }
     }
-
-    tables.extend(target_dependency_tables);
-    tables.extend(target_dev_dependency_tables);
-
-    tables
 }
-
-/// Trims off the optional 'dep:' prefix and the '/<feature>' suffix.
-fn trim_feature(mut s: &str) -> &str {
-    s = s.split_once("dep:")
-        .map(|(_l, r)| r)
-        .unwrap_or(s);
-    s = s.split_once('/')
-        .map(|(l, _r)| l)
-        .unwrap_or(s);
-    s
-}
\ No newline at end of file
@@ -2,21 +2,35 @@ use std::collections::BTreeSet;
 
 use petgraph::Direction;
 
-use crate::feature_dependencies;
+use crate::feature_dependencies::{self, DependencyKind, Node};
 
+/// Finds the nodes which are enabled by the explicit features.
+///
+/// A feature of a dependency behind a weak edge is only enabled once its dependency is enabled by something else.
 pub fn from_dependency_graph<'a>(
     explicit_features: impl Iterator<Item = &'a str>,
-    dependency_graph: &'a feature_dependencies::Graph
-) -> BTreeSet<&'a str> {
-    let mut stack = explicit_features.collect::<Vec<_>>();
+    dependency_graph: &feature_dependencies::Graph<'a>
+) -> BTreeSet<Node<'a>> {
+    let mut stack = explicit_features.map(Node::Feature).collect::<Vec<_>>();
     let mut visited_features = BTreeSet::new();
-    while let Some(feature) = stack.pop() {
-        if !visited_features.contains(feature) {
-            visited_features.insert(feature);
-            let new_features = dependency_graph.neighbors_directed(feature, Direction::Outgoing)
-                .filter(|&f| dependency_graph.contains_node(f));
-            stack.extend(new_features);
+    let mut weak_features = vec![];
+    while !stack.is_empty() {
+        while let Some(feature) = stack.pop() {
+            if visited_features.insert(feature) {
+                for (_, dependency, &kind) in dependency_graph.edges_directed(feature, Direction::Outgoing) {
+                    match kind {
+                        DependencyKind::Strong => stack.push(dependency),
+                        DependencyKind::Weak => weak_features.push(dependency),
+                    }
+                }
+            }
         }
+
+        stack.extend(weak_features.iter()
+            .copied()
+            .filter(|f| !visited_features.contains(f))
+            .filter(|&f| feature_dependencies::dependency_node(dependency_graph, f).is_some_and(|d| visited_features.contains(&d)))
+        );
     }
 
     visited_features
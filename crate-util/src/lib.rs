@@ -1,7 +1,8 @@
 pub mod feature_dependencies;
 pub mod implied_features;
-pub mod toml_util;
+pub mod manifest;
 pub mod crate_id;
+pub mod csvconf;
 
 use std::{fs::File, io::{Cursor, Read}, path::{Path, PathBuf}, time::Duration};
 
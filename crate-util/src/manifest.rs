@@ -0,0 +1,312 @@
+use std::{collections::BTreeMap, fmt, path::{Path, PathBuf}, str::FromStr};
+
+use serde::{Deserialize, de::{self, DeserializeOwned}};
+use toml::Spanned;
+
+mod workspace;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The manifest isn't valid TOML, or a value has the wrong type. The error shows where in the manifest it failed.
+    #[error(transparent)]
+    Parse(#[from] toml::de::Error),
+    #[error("Failed to parse {0:?}: {1}")]
+    ParseFile(PathBuf, toml::de::Error),
+    #[error("Failed to read {0:?}")]
+    Io(PathBuf, #[source] std::io::Error),
+    #[error("The manifest has no [package] table")]
+    MissingPackage,
+    #[error("{0:?} inherits from its workspace, but no workspace root was found")]
+    MissingWorkspace(PathBuf),
+    #[error("{path:?}, line {line}, column {column}: {key} is inherited from the workspace, but the workspace at {root:?} doesn't define it")]
+    NotInWorkspace {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        key: String,
+        root: PathBuf,
+    },
+}
+
+/// The parts of a Cargo.toml which are relevant to features, parsed with the positions of the values.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Manifest {
+    pub package: Option<Package>,
+    #[serde(default)]
+    pub features: BTreeMap<String, Vec<Spanned<String>>>,
+    #[serde(default)]
+    pub dependencies: Dependencies,
+    #[serde(default, alias = "dev_dependencies")]
+    pub dev_dependencies: Dependencies,
+    #[serde(default, alias = "build_dependencies")]
+    pub build_dependencies: Dependencies,
+    /// The platform specific dependencies by their target, like 'cfg(unix)'.
+    #[serde(default)]
+    pub target: BTreeMap<String, Target>,
+    /// Only present in the root manifest of a workspace.
+    pub workspace: Option<Workspace>,
+}
+
+/// The dependencies of one kind, by the name which the crate uses for them.
+pub type Dependencies = BTreeMap<String, Spanned<Dependency>>;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Package {
+    pub name: Spanned<String>,
+    pub version: Option<Spanned<Inheritable<String>>>,
+    /// The path to the root of the workspace, if it isn't the closest directory above with a workspace manifest.
+    pub workspace: Option<PathBuf>,
+}
+
+/// The [workspace] table of a root manifest, with the values which members can inherit.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Workspace {
+    #[serde(default)]
+    pub package: WorkspacePackage,
+    #[serde(default)]
+    pub dependencies: Dependencies,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WorkspacePackage {
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Target {
+    #[serde(default)]
+    pub dependencies: Dependencies,
+    #[serde(default, alias = "dev_dependencies")]
+    pub dev_dependencies: Dependencies,
+    #[serde(default, alias = "build_dependencies")]
+    pub build_dependencies: Dependencies,
+}
+
+/// A dependency, given either as a version requirement or as a table.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dependency {
+    pub version: Option<String>,
+    /// The name of the package, if the dependency is renamed, like `tokio1 = { package = "tokio" }`.
+    pub package: Option<String>,
+    pub optional: bool,
+    pub features: Vec<String>,
+    pub default_features: Option<bool>,
+    /// Marks a dependency which is inherited from [workspace.dependencies], like `serde = { workspace = true }`.
+    pub workspace: bool,
+}
+
+/// A value of the package which may be inherited from the workspace, like `version.workspace = true`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Inheritable<T> {
+    Value(T),
+    Workspace,
+}
+
+impl Manifest {
+    /// Reads the manifest at the path, and resolves the values which it inherits from its workspace,
+    /// so the manifest doesn't contain [Inheritable::Workspace] or dependencies with `workspace = true` anymore.
+    pub fn read(path: &Path) -> Result<Manifest, Error> {
+        let (mut manifest, source) = parse_file(path)?;
+        if manifest.inherits() {
+            workspace::resolve(&mut manifest, path, &source)?;
+        }
+
+        Ok(manifest)
+    }
+
+    pub fn package(&self) -> Result<&Package, Error> {
+        self.package.as_ref()
+            .ok_or(Error::MissingPackage)
+    }
+
+    /// Determines if any value of the manifest is inherited from its workspace.
+    pub fn inherits(&self) -> bool {
+        let version = self.package.as_ref()
+            .and_then(|p| p.version.as_ref())
+            .is_some_and(|v| *v.get_ref() == Inheritable::Workspace);
+        version || self.all_dependencies().any(|(_, dependency)| dependency.workspace)
+    }
+
+    /// All dependencies of every kind, including the platform specific ones.
+    /// A dependency may be listed several times, like in both dependencies and dev-dependencies.
+    pub fn all_dependencies(&self) -> impl Iterator<Item = (&str, &Dependency)> {
+        let target_tables = self.target.values()
+            .flat_map(|t| [&t.dependencies, &t.dev_dependencies, &t.build_dependencies]);
+        [&self.dependencies, &self.dev_dependencies, &self.build_dependencies].into_iter()
+            .chain(target_tables)
+            .flatten()
+            .map(|(name, dependency)| (name.as_str(), dependency.get_ref()))
+    }
+
+    fn dependency_tables_mut(&mut self) -> impl Iterator<Item = &mut Dependencies> {
+        let target_tables = self.target.values_mut()
+            .flat_map(|t| [&mut t.dependencies, &mut t.dev_dependencies, &mut t.build_dependencies]);
+        [&mut self.dependencies, &mut self.dev_dependencies, &mut self.build_dependencies].into_iter()
+            .chain(target_tables)
+    }
+}
+
+/// Parses the manifest at the path as it is, together with its source.
+fn parse_file(path: &Path) -> Result<(Manifest, String), Error> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|e| Error::Io(path.to_owned(), e))?;
+    let manifest = toml::from_str(&source)
+        .map_err(|e| Error::ParseFile(path.to_owned(), e))?;
+    Ok((manifest, source))
+}
+
+impl FromStr for Manifest {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+impl Dependency {
+    /// The name of the package behind the dependency, which differs from its name in the manifest if it's renamed.
+    pub fn package_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.package.as_deref().unwrap_or(name)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct DependencyTable {
+    version: Option<String>,
+    package: Option<String>,
+    #[serde(default)]
+    optional: bool,
+    #[serde(default)]
+    features: Vec<String>,
+    #[serde(alias = "default_features")]
+    default_features: Option<bool>,
+    #[serde(default)]
+    workspace: bool,
+}
+
+impl<'de> Deserialize<'de> for Dependency {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Dependency;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a version requirement or a dependency table")
+            }
+
+            fn visit_str<E: de::Error>(self, version: &str) -> Result<Self::Value, E> {
+                Ok(Dependency { version: Some(version.to_owned()), ..Dependency::default() })
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                let table = DependencyTable::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                Ok(Dependency {
+                    version: table.version,
+                    package: table.package,
+                    optional: table.optional,
+                    features: table.features,
+                    default_features: table.default_features,
+                    workspace: table.workspace,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for Inheritable<T> {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match toml::Value::deserialize(deserializer)? {
+            toml::Value::Table(table) if table.contains_key("workspace") => match table.get("workspace") {
+                Some(toml::Value::Boolean(true)) if table.len() == 1 => Ok(Inheritable::Workspace),
+                _ => Err(de::Error::custom("expected `workspace = true`")),
+            },
+            value => T::deserialize(value)
+                .map(Inheritable::Value)
+                .map_err(de::Error::custom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dependency, Error, Inheritable, Manifest};
+
+    #[test]
+    fn parses_packages_features_and_dependencies() {
+        let source = r#"[package]
+name = "example"
+version = "1.2.3"
+
+[features]
+default = ["std"]
+std = ["dep:serde", "chrono?/serde"]
+
+[dependencies]
+serde = { version = "1", optional = true, default-features = false, features = ["derive"] }
+tokio1 = { package = "tokio", version = "1" }
+
+[dev-dependencies]
+chrono = "0.4"
+
+[target.'cfg(unix)'.build_dependencies]
+libc = { version = "0.2", default_features = true }
+"#;
+        let manifest: Manifest = source.parse().unwrap();
+
+        let package = manifest.package().unwrap();
+        assert_eq!(package.name.get_ref(), "example");
+        assert_eq!(*package.version.as_ref().unwrap().get_ref(), Inheritable::Value("1.2.3".to_owned()));
+        assert!(!manifest.inherits());
+
+        let std = manifest.features["std"].iter().map(|s| s.get_ref().as_str()).collect::<Vec<_>>();
+        assert_eq!(std, ["dep:serde", "chrono?/serde"]);
+        assert_eq!(&source[manifest.features["default"][0].span()], "\"std\"");
+
+        assert_eq!(*manifest.dependencies["serde"].get_ref(), Dependency {
+            version: Some("1".to_owned()),
+            optional: true,
+            features: vec!["derive".to_owned()],
+            default_features: Some(false),
+            ..Dependency::default()
+        });
+        assert_eq!(manifest.dependencies["tokio1"].get_ref().package_name("tokio1"), "tokio");
+        assert_eq!(manifest.dev_dependencies["chrono"].get_ref().version.as_deref(), Some("0.4"));
+        assert_eq!(manifest.target["cfg(unix)"].build_dependencies["libc"].get_ref().default_features, Some(true));
+
+        let names = manifest.all_dependencies().map(|(name, _)| name).collect::<Vec<_>>();
+        assert_eq!(names, ["serde", "tokio1", "chrono", "libc"]);
+    }
+
+    #[test]
+    fn parses_inherited_values() {
+        let manifest: Manifest = r#"[package]
+name = "member"
+version.workspace = true
+
+[dependencies]
+serde = { workspace = true, optional = true }
+"#.parse().unwrap();
+
+        assert_eq!(*manifest.package().unwrap().version.as_ref().unwrap().get_ref(), Inheritable::Workspace);
+        assert!(manifest.dependencies["serde"].get_ref().workspace);
+        assert!(manifest.inherits());
+    }
+
+    #[test]
+    fn rejects_invalid_manifests() {
+        let wrong_type = "[package]\nname = \"example\"\n\n[dependencies]\nserde = 1\n".parse::<Manifest>();
+        assert!(matches!(wrong_type, Err(Error::Parse(e)) if e.to_string().contains("line 5")));
+
+        let not_inherited = "[package]\nname = \"example\"\nversion = { workspace = false }\n".parse::<Manifest>();
+        assert!(matches!(not_inherited, Err(Error::Parse(e)) if e.to_string().contains("expected `workspace = true`")));
+
+        let no_package = "[features]\n".parse::<Manifest>().unwrap();
+        assert!(matches!(no_package.package(), Err(Error::MissingPackage)));
+    }
+}
@@ -0,0 +1,242 @@
+use std::{ops::Range, path::{Path, PathBuf}};
+
+use itertools::Itertools;
+use toml::Spanned;
+
+use super::{Dependency, Error, Inheritable, Manifest, parse_file};
+
+/// Replaces the inherited version and dependencies of a member by the values of its workspace.
+pub(super) fn resolve(manifest: &mut Manifest, path: &Path, source: &str) -> Result<(), Error> {
+    let (root_path, root) = find_root(manifest, path)?;
+    let workspace = root.workspace.unwrap_or_default();
+    let not_in_workspace = |span: Range<usize>, key: String| {
+        let (line, column) = position(source, span.start);
+        Error::NotInWorkspace { path: path.to_owned(), line, column, key, root: root_path.clone() }
+    };
+
+    if let Some(version) = manifest.package.as_mut().and_then(|p| p.version.as_mut())
+        && *version.get_ref() == Inheritable::Workspace {
+        let inherited = workspace.package.version.clone()
+            .ok_or_else(|| not_in_workspace(version.span(), "version".to_owned()))?;
+        *version = Spanned::new(version.span(), Inheritable::Value(inherited));
+    }
+
+    for dependencies in manifest.dependency_tables_mut() {
+        for (name, dependency) in dependencies.iter_mut().filter(|(_, d)| d.get_ref().workspace) {
+            let inherited = workspace.dependencies.get(name)
+                .ok_or_else(|| not_in_workspace(dependency.span(), format!("dependency {name}")))?;
+            let span = dependency.span();
+            *dependency = Spanned::new(span, inherit(dependency.get_ref(), inherited.get_ref()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Combines a dependency of a member with the dependency of the workspace which it inherits.
+///
+/// The member decides if the dependency is optional and adds its features to those of the workspace,
+/// and it can only enable the default features if the workspace disables them.
+fn inherit(dependency: &Dependency, inherited: &Dependency) -> Dependency {
+    let default_features = match inherited.default_features {
+        Some(false) => dependency.default_features.or(Some(false)),
+        default_features => default_features,
+    };
+
+    Dependency {
+        version: inherited.version.clone(),
+        package: inherited.package.clone(),
+        optional: dependency.optional,
+        features: inherited.features.iter()
+            .chain(&dependency.features)
+            .unique()
+            .cloned()
+            .collect(),
+        default_features,
+        workspace: false,
+    }
+}
+
+/// Finds the root manifest of the workspace of a member, which is the member itself if it has a [workspace] table,
+/// the manifest in the directory given by package.workspace, or otherwise the closest manifest above the member with a [workspace] table.
+/// The root isn't resolved itself, since it can't inherit from another workspace.
+fn find_root(member: &Manifest, path: &Path) -> Result<(PathBuf, Manifest), Error> {
+    let path = std::fs::canonicalize(path)
+        .map_err(|e| Error::Io(path.to_owned(), e))?;
+    if member.workspace.is_some() {
+        return Ok((path, member.clone()));
+    }
+
+    let directory = path.parent()
+        .expect("A manifest is a file in a directory");
+    if let Some(root) = member.package.as_ref().and_then(|p| p.workspace.as_ref()) {
+        let root_path = directory.join(root).join("Cargo.toml");
+        let (root, _) = parse_file(&root_path)?;
+        return Ok((root_path, root));
+    }
+
+    for ancestor in directory.ancestors().skip(1) {
+        let root_path = ancestor.join("Cargo.toml");
+        if root_path.is_file() {
+            let (root, _) = parse_file(&root_path)?;
+            if root.workspace.is_some() {
+                return Ok((root_path, root));
+            }
+        }
+    }
+
+    Err(Error::MissingWorkspace(path))
+}
+
+/// The line and column of a byte offset in the source, both starting at 1.
+fn position(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::manifest::{Dependency, Error, Inheritable, Manifest};
+
+    use super::{inherit, position};
+
+    /// A directory below the temporary directory which is removed again when it's dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> TempDir {
+            let path = std::env::temp_dir().join(format!("crate-util-{name}-{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+
+        fn write(&self, path: &str, content: &str) -> PathBuf {
+            let path = self.0.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, content).unwrap();
+            path
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    const ROOT: &str = r#"[workspace]
+members = ["member"]
+
+[workspace.package]
+version = "0.3.0"
+
+[workspace.dependencies]
+serde = { version = "1", default-features = false, features = ["derive"] }
+tokio = { version = "1", features = ["rt"] }
+"#;
+
+    const MEMBER: &str = r#"[package]
+name = "member"
+version.workspace = true
+
+[dependencies]
+serde = { workspace = true, optional = true, features = ["std", "derive"] }
+
+[target.'cfg(unix)'.dev-dependencies]
+tokio = { workspace = true, default-features = false }
+"#;
+
+    fn version(manifest: &Manifest) -> &Inheritable<String> {
+        manifest.package().unwrap().version.as_ref().unwrap().get_ref()
+    }
+
+    fn assert_resolved(manifest: &Manifest) {
+        assert_eq!(*version(manifest), Inheritable::Value("0.3.0".to_owned()));
+        assert!(!manifest.inherits());
+        assert_eq!(*manifest.dependencies["serde"].get_ref(), Dependency {
+            version: Some("1".to_owned()),
+            optional: true,
+            features: vec!["derive".to_owned(), "std".to_owned()],
+            default_features: Some(false),
+            ..Dependency::default()
+        });
+        let tokio = manifest.target["cfg(unix)"].dev_dependencies["tokio"].get_ref();
+        assert_eq!(tokio.features, ["rt"]);
+        assert_eq!(tokio.default_features, None);
+    }
+
+    #[test]
+    fn positions_start_at_one() {
+        let source = "a\nbc\n\u{e4}d";
+        assert_eq!(position(source, 0), (1, 1));
+        assert_eq!(position(source, 1), (1, 2));
+        assert_eq!(position(source, 2), (2, 1));
+        assert_eq!(position(source, 3), (2, 2));
+        assert_eq!(position(source, 7), (3, 2));
+    }
+
+    #[test]
+    fn members_only_enable_default_features_which_the_workspace_disables() {
+        let dependency = |default_features| Dependency { default_features, ..Dependency::default() };
+        let cases = [
+            (None, None, None),
+            (None, Some(false), None),
+            (Some(true), Some(false), Some(true)),
+            (Some(false), None, Some(false)),
+            (Some(false), Some(true), Some(true)),
+            (Some(false), Some(false), Some(false)),
+        ];
+        for (workspace, member, expected) in cases {
+            assert_eq!(inherit(&dependency(member), &dependency(workspace)).default_features, expected, "{workspace:?} {member:?}");
+        }
+    }
+
+    #[test]
+    fn resolves_from_the_closest_workspace_above() {
+        let directory = TempDir::new("closest");
+        directory.write("Cargo.toml", ROOT);
+        let path = directory.write("member/Cargo.toml", MEMBER);
+
+        assert_resolved(&Manifest::read(&path).unwrap());
+    }
+
+    #[test]
+    fn resolves_from_the_workspace_of_the_package() {
+        let directory = TempDir::new("package-workspace");
+        directory.write("Cargo.toml", "[workspace]\n");
+        directory.write("root/Cargo.toml", ROOT);
+        let member = MEMBER.replace("version.workspace = true", "version.workspace = true\nworkspace = \"../../root\"");
+        let path = directory.write("crates/member/Cargo.toml", &member);
+
+        assert_resolved(&Manifest::read(&path).unwrap());
+    }
+
+    #[test]
+    fn reports_values_which_are_not_in_the_workspace() {
+        let directory = TempDir::new("not-in-workspace");
+        let root = directory.write("Cargo.toml", "[workspace]\n\n[workspace.package]\nversion = \"0.3.0\"\n");
+        let path = directory.write("member/Cargo.toml", MEMBER);
+
+        match Manifest::read(&path) {
+            Err(Error::NotInWorkspace { path: error_path, line, column, key, root: error_root }) => {
+                assert_eq!(error_path, path);
+                assert_eq!((line, column), (6, 9));
+                assert_eq!(key, "dependency serde");
+                assert_eq!(error_root, std::fs::canonicalize(root).unwrap());
+            }
+            result => panic!("Expected NotInWorkspace, found {result:?}"),
+        }
+    }
+
+    #[test]
+    fn reports_members_without_workspace() {
+        let directory = TempDir::new("missing-workspace");
+        let path = directory.write("member/Cargo.toml", MEMBER);
+
+        assert!(matches!(Manifest::read(&path), Err(Error::MissingWorkspace(p)) if p.ends_with(Path::new("member/Cargo.toml"))));
+    }
+}
@@ -0,0 +1,59 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// A typed value attached to a feature, such as how often the feature is used.
+/// In JSON, attributes are written as plain booleans, numbers and strings,
+/// where numbers without a fractional part or exponent are integers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Attribute {
+    Bool(bool),
+    Integer(i64),
+    Real(f64),
+    String(String),
+}
+
+impl From<bool> for Attribute {
+    fn from(value: bool) -> Self {
+        Attribute::Bool(value)
+    }
+}
+
+impl From<i64> for Attribute {
+    fn from(value: i64) -> Self {
+        Attribute::Integer(value)
+    }
+}
+
+impl From<f64> for Attribute {
+    fn from(value: f64) -> Self {
+        Attribute::Real(value)
+    }
+}
+
+impl From<String> for Attribute {
+    fn from(value: String) -> Self {
+        Attribute::String(value)
+    }
+}
+
+impl From<&str> for Attribute {
+    fn from(value: &str) -> Self {
+        Attribute::String(value.to_owned())
+    }
+}
+
+/// Writes the value as a UVL literal, where strings are enclosed in single quotes with backslashes and quotes escaped by a backslash,
+/// and reals are written as plain decimals with a decimal point, so they can be told apart from integers.
+impl Display for Attribute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Attribute::Bool(value) => write!(f, "{value}"),
+            Attribute::Integer(value) => write!(f, "{value}"),
+            Attribute::Real(value) if value.fract() == 0.0 => write!(f, "{value}.0"),
+            Attribute::Real(value) => write!(f, "{value}"),
+            Attribute::String(value) => write!(f, "'{}'", value.replace('\\', "\\\\").replace('\'', "\\'")),
+        }
+    }
+}
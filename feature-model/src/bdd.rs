@@ -0,0 +1,480 @@
+use std::collections::{HashMap, HashSet};
+
+use num_bigint::BigUint;
+
+/// Reference to a node inside a [Bdd].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    pub const FALSE: NodeId = NodeId(0);
+    pub const TRUE: NodeId = NodeId(1);
+
+    pub fn is_terminal(self) -> bool {
+        self.0 < 2
+    }
+}
+
+/// Variable index used for the terminal nodes, which are ordered after every variable.
+const TERMINAL: u32 = u32::MAX;
+
+#[derive(Clone, Copy)]
+struct Node {
+    variable: u32,
+    low: NodeId,
+    high: NodeId,
+}
+
+/// A reduced ordered binary decision diagram manager.
+///
+/// Every function is represented by a [NodeId] owned by the manager.
+/// Because nodes are hash-consed, two functions are equivalent if and only if their ids are equal.
+/// Variables are ordered by their index, so variables that are related should be created close to each other.
+pub struct Bdd {
+    variable_count: u32,
+    nodes: Vec<Node>,
+    unique_table: HashMap<(u32, NodeId, NodeId), NodeId>,
+    ite_cache: HashMap<(NodeId, NodeId, NodeId), NodeId>,
+}
+
+impl Default for Bdd {
+    fn default() -> Self {
+        Bdd::new()
+    }
+}
+
+impl Bdd {
+    pub fn new() -> Bdd {
+        let terminal = |id: NodeId| Node { variable: TERMINAL, low: id, high: id };
+        Bdd {
+            variable_count: 0,
+            nodes: vec![terminal(NodeId::FALSE), terminal(NodeId::TRUE)],
+            unique_table: HashMap::new(),
+            ite_cache: HashMap::new(),
+        }
+    }
+
+    /// Creates a new variable, which is ordered after all existing variables.
+    pub fn new_variable(&mut self) -> u32 {
+        self.variable_count += 1;
+        self.variable_count - 1
+    }
+
+    pub fn variable_count(&self) -> u32 {
+        self.variable_count
+    }
+
+    /// Returns the function which is true when the variable is true.
+    pub fn variable(&mut self, variable: u32) -> NodeId {
+        self.make(variable, NodeId::FALSE, NodeId::TRUE)
+    }
+
+    /// Returns the function which is true when the variable has the given value.
+    pub fn literal(&mut self, variable: u32, value: bool) -> NodeId {
+        if value {
+            self.make(variable, NodeId::FALSE, NodeId::TRUE)
+        } else {
+            self.make(variable, NodeId::TRUE, NodeId::FALSE)
+        }
+    }
+
+    /// The variable at the top of the node, or None for terminals.
+    pub fn top_variable(&self, f: NodeId) -> Option<u32> {
+        Some(self.nodes[f.0 as usize].variable).filter(|&v| v != TERMINAL)
+    }
+
+    /// The children of a node when the top variable is false and true respectively.
+    pub fn children(&self, f: NodeId) -> (NodeId, NodeId) {
+        let node = self.nodes[f.0 as usize];
+        (node.low, node.high)
+    }
+
+    pub fn not(&mut self, f: NodeId) -> NodeId {
+        self.ite(f, NodeId::FALSE, NodeId::TRUE)
+    }
+
+    pub fn and(&mut self, f: NodeId, g: NodeId) -> NodeId {
+        self.ite(f, g, NodeId::FALSE)
+    }
+
+    pub fn or(&mut self, f: NodeId, g: NodeId) -> NodeId {
+        self.ite(f, NodeId::TRUE, g)
+    }
+
+    pub fn implies(&mut self, f: NodeId, g: NodeId) -> NodeId {
+        self.ite(f, g, NodeId::TRUE)
+    }
+
+    pub fn equivalent(&mut self, f: NodeId, g: NodeId) -> NodeId {
+        let not_g = self.not(g);
+        self.ite(f, g, not_g)
+    }
+
+    /// If-then-else, the operator which every other boolean operator is built upon.
+    pub fn ite(&mut self, f: NodeId, g: NodeId, h: NodeId) -> NodeId {
+        if f == NodeId::TRUE {
+            return g;
+        }
+        if f == NodeId::FALSE {
+            return h;
+        }
+        if g == h {
+            return g;
+        }
+        if g == NodeId::TRUE && h == NodeId::FALSE {
+            return f;
+        }
+
+        if let Some(&result) = self.ite_cache.get(&(f, g, h)) {
+            return result;
+        }
+
+        let variable = [f, g, h].iter()
+            .map(|&n| self.nodes[n.0 as usize].variable)
+            .min()
+            .expect("Array is not empty");
+        let (f_low, f_high) = self.cofactors(f, variable);
+        let (g_low, g_high) = self.cofactors(g, variable);
+        let (h_low, h_high) = self.cofactors(h, variable);
+        let low = self.ite(f_low, g_low, h_low);
+        let high = self.ite(f_high, g_high, h_high);
+        let result = self.make(variable, low, high);
+
+        self.ite_cache.insert((f, g, h), result);
+        result
+    }
+
+    /// Fixes the value of a variable in the function.
+    pub fn restrict(&mut self, f: NodeId, variable: u32, value: bool) -> NodeId {
+        let mut cache = HashMap::new();
+        self.restrict_cached(f, variable, value, &mut cache)
+    }
+
+    fn restrict_cached(&mut self, f: NodeId, variable: u32, value: bool, cache: &mut HashMap<NodeId, NodeId>) -> NodeId {
+        let node = self.nodes[f.0 as usize];
+        if node.variable > variable {
+            return f;
+        }
+        if node.variable == variable {
+            return if value { node.high } else { node.low };
+        }
+        if let Some(&result) = cache.get(&f) {
+            return result;
+        }
+
+        let low = self.restrict_cached(node.low, variable, value, cache);
+        let high = self.restrict_cached(node.high, variable, value, cache);
+        let result = self.make(node.variable, low, high);
+        cache.insert(f, result);
+        result
+    }
+
+    /// Existentially quantifies the variables for which the predicate returns true.
+    pub fn exists(&mut self, f: NodeId, quantify: impl Fn(u32) -> bool) -> NodeId {
+        let mut cache = HashMap::new();
+        self.exists_cached(f, &quantify, &mut cache)
+    }
+
+    fn exists_cached(&mut self, f: NodeId, quantify: &impl Fn(u32) -> bool, cache: &mut HashMap<NodeId, NodeId>) -> NodeId {
+        if f.is_terminal() {
+            return f;
+        }
+        if let Some(&result) = cache.get(&f) {
+            return result;
+        }
+
+        let node = self.nodes[f.0 as usize];
+        let low = self.exists_cached(node.low, quantify, cache);
+        let high = self.exists_cached(node.high, quantify, cache);
+        let result = if quantify(node.variable) {
+            self.or(low, high)
+        } else {
+            self.make(node.variable, low, high)
+        };
+        cache.insert(f, result);
+        result
+    }
+
+    /// Counts the number of satisfying assignments over all variables of the manager.
+    pub fn count(&self, f: NodeId) -> BigUint {
+        let mut cache = HashMap::new();
+        let count = self.count_cached(f, &mut cache);
+        count << self.level(f)
+    }
+
+    /// Counts the satisfying assignments over the variables from the top variable of f and below.
+    fn count_cached(&self, f: NodeId, cache: &mut HashMap<NodeId, BigUint>) -> BigUint {
+        if f == NodeId::FALSE {
+            return BigUint::ZERO;
+        }
+        if f == NodeId::TRUE {
+            return BigUint::from(1_u32);
+        }
+        if let Some(count) = cache.get(&f) {
+            return count.clone();
+        }
+
+        let node = self.nodes[f.0 as usize];
+        let low = self.count_cached(node.low, cache) << (self.level(node.low) - node.variable - 1);
+        let high = self.count_cached(node.high, cache) << (self.level(node.high) - node.variable - 1);
+        let count = low + high;
+        cache.insert(f, count.clone());
+        count
+    }
+
+    /// Finds a satisfying assignment of the function.
+    /// Variables which can take any value are set to false.
+    pub fn any_sat(&self, f: NodeId) -> Option<Vec<bool>> {
+        if f == NodeId::FALSE {
+            return None;
+        }
+
+        let mut assignment = vec![false; self.variable_count as usize];
+        let mut node = f;
+        while !node.is_terminal() {
+            let Node { variable, low, high } = self.nodes[node.0 as usize];
+            if low != NodeId::FALSE {
+                node = low;
+            } else {
+                assignment[variable as usize] = true;
+                node = high;
+            }
+        }
+
+        Some(assignment)
+    }
+
+    /// Determines for every variable if the function can be satisfied with the variable false and true respectively,
+    /// which is the same as restricting the function by every literal, but in a single traversal.
+    ///
+    /// Every node other than the false terminal is part of a satisfying path,
+    /// so a literal is satisfiable if a reachable node takes its branch, or a reachable edge skips its variable.
+    pub fn satisfiable_literals(&self, f: NodeId) -> Vec<[bool; 2]> {
+        let mut literals = vec![[false; 2]; self.variable_count as usize];
+        if f == NodeId::FALSE {
+            return literals;
+        }
+
+        // skipped[v] counts the edges which start skipping variables at v, minus those which stop skipping at v.
+        let mut skipped = vec![0_i64; self.variable_count as usize + 1];
+        skipped[0] += 1;
+        skipped[self.level(f) as usize] -= 1;
+
+        let mut visited = HashSet::from([f]);
+        let mut stack = vec![f];
+        while let Some(node) = stack.pop() {
+            if node.is_terminal() {
+                continue;
+            }
+
+            let Node { variable, low, high } = self.nodes[node.0 as usize];
+            for (value, child) in [(false, low), (true, high)] {
+                if child == NodeId::FALSE {
+                    continue;
+                }
+
+                literals[variable as usize][value as usize] = true;
+                skipped[variable as usize + 1] += 1;
+                skipped[self.level(child) as usize] -= 1;
+                if visited.insert(child) {
+                    stack.push(child);
+                }
+            }
+        }
+
+        let mut skipping = 0;
+        for (variable, literal) in literals.iter_mut().enumerate() {
+            skipping += skipped[variable];
+            if skipping > 0 {
+                *literal = [true; 2];
+            }
+        }
+
+        literals
+    }
+
+    /// The level of a node, where terminals are placed below every variable.
+    fn level(&self, f: NodeId) -> u32 {
+        self.top_variable(f).unwrap_or(self.variable_count)
+    }
+
+    fn cofactors(&self, f: NodeId, variable: u32) -> (NodeId, NodeId) {
+        let node = self.nodes[f.0 as usize];
+        if node.variable == variable {
+            (node.low, node.high)
+        } else {
+            (f, f)
+        }
+    }
+
+    /// Finds or creates the node with the given variable and children.
+    /// The children must only depend on variables ordered after the given variable.
+    pub(crate) fn make(&mut self, variable: u32, low: NodeId, high: NodeId) -> NodeId {
+        if low == high {
+            return low;
+        }
+
+        *self.unique_table.entry((variable, low, high))
+            .or_insert_with(|| {
+                self.nodes.push(Node { variable, low, high });
+                NodeId(self.nodes.len() as u32 - 1)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::{Bdd, NodeId};
+
+    const VARIABLES: u32 = 4;
+
+    /// Functions over four variables as truth tables,
+    /// where bit i is the value for the assignment which sets variable v to bit v of i.
+    fn tables() -> Vec<u16> {
+        (0..40_u32)
+            .map(|i| (i.wrapping_mul(40503).wrapping_add(12345) >> 3) as u16)
+            .chain([0, u16::MAX, 0xaaaa, 0x00ff])
+            .collect()
+    }
+
+    fn manager() -> Bdd {
+        let mut bdd = Bdd::new();
+        for _ in 0..VARIABLES {
+            bdd.new_variable();
+        }
+        bdd
+    }
+
+    /// Builds the function of a truth table by Shannon expansion, without using any operator of the manager.
+    fn build(bdd: &mut Bdd, table: u16) -> NodeId {
+        fn expand(bdd: &mut Bdd, table: u16, variable: u32, assignment: u32) -> NodeId {
+            if variable == VARIABLES {
+                return if table >> assignment & 1 == 1 { NodeId::TRUE } else { NodeId::FALSE };
+            }
+            let low = expand(bdd, table, variable + 1, assignment);
+            let high = expand(bdd, table, variable + 1, assignment | 1 << variable);
+            bdd.make(variable, low, high)
+        }
+        expand(bdd, table, 0, 0)
+    }
+
+    fn evaluate(bdd: &Bdd, mut f: NodeId, assignment: u32) -> bool {
+        while let Some(variable) = bdd.top_variable(f) {
+            let (low, high) = bdd.children(f);
+            f = if assignment >> variable & 1 == 1 { high } else { low };
+        }
+        f == NodeId::TRUE
+    }
+
+    fn table(f: impl Fn(u32) -> bool) -> u16 {
+        (0..1_u32 << VARIABLES).fold(0, |table, assignment| table | (f(assignment) as u16) << assignment)
+    }
+
+    fn bit(table: u16, assignment: u32) -> bool {
+        table >> assignment & 1 == 1
+    }
+
+    #[test]
+    fn functions_are_canonical() {
+        let mut bdd = manager();
+        for t in tables() {
+            let f = build(&mut bdd, t);
+            assert_eq!(table(|a| evaluate(&bdd, f, a)), t);
+            assert_eq!(build(&mut bdd, t), f);
+        }
+    }
+
+    #[test]
+    fn operators_match_truth_tables() {
+        let mut bdd = manager();
+        let tables = tables();
+        for &a in &tables {
+            let f = build(&mut bdd, a);
+            let not = build(&mut bdd, !a);
+            assert_eq!(bdd.not(f), not);
+
+            for &b in &tables {
+                let g = build(&mut bdd, b);
+                let cases = [
+                    (bdd.and(f, g), a & b),
+                    (bdd.or(f, g), a | b),
+                    (bdd.implies(f, g), !a | b),
+                    (bdd.equivalent(f, g), !(a ^ b)),
+                ];
+                for (result, expected) in cases {
+                    assert_eq!(result, build(&mut bdd, expected));
+                }
+            }
+
+            for (&b, &c) in tables.iter().zip(tables.iter().rev()) {
+                let g = build(&mut bdd, b);
+                let h = build(&mut bdd, c);
+                let expected = build(&mut bdd, (a & b) | (!a & c));
+                assert_eq!(bdd.ite(f, g, h), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn restrict_and_exists_match_truth_tables() {
+        let mut bdd = manager();
+        for t in tables() {
+            let f = build(&mut bdd, t);
+            for variable in 0..VARIABLES {
+                for value in [false, true] {
+                    let expected = table(|a| bit(t, if value { a | 1 << variable } else { a & !(1 << variable) }));
+                    let expected = build(&mut bdd, expected);
+                    assert_eq!(bdd.restrict(f, variable, value), expected);
+                }
+            }
+
+            for quantified in 0..1_u32 << VARIABLES {
+                let expected = table(|a| (0..1_u32 << VARIABLES)
+                    .filter(|&other| other & !quantified == a & !quantified)
+                    .any(|other| bit(t, other)));
+                let expected = build(&mut bdd, expected);
+                assert_eq!(bdd.exists(f, |v| quantified >> v & 1 == 1), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn count_and_any_sat_match_truth_tables() {
+        let mut bdd = manager();
+        for t in tables() {
+            let f = build(&mut bdd, t);
+            assert_eq!(bdd.count(f), BigUint::from(t.count_ones()));
+
+            match bdd.any_sat(f) {
+                Some(assignment) => {
+                    let assignment = assignment.iter().enumerate().fold(0, |a, (v, &value)| a | (value as u32) << v);
+                    assert!(bit(t, assignment));
+                }
+                None => assert_eq!(t, 0),
+            }
+        }
+
+        // Variables created after a function are free in it.
+        let f = build(&mut bdd, 0x8000);
+        bdd.new_variable();
+        assert_eq!(bdd.count(f), BigUint::from(2_u32));
+    }
+
+    #[test]
+    fn satisfiable_literals_match_truth_tables() {
+        let mut bdd = manager();
+        for t in tables() {
+            let f = build(&mut bdd, t);
+            let literals = bdd.satisfiable_literals(f);
+            for variable in 0..VARIABLES {
+                for value in [false, true] {
+                    let expected = (0..1_u32 << VARIABLES)
+                        .any(|a| bit(t, a) && (a >> variable & 1 == 1) == value);
+                    assert_eq!(literals[variable as usize][value as usize], expected, "{t:#06x} {variable} {value}");
+                }
+            }
+        }
+    }
+}
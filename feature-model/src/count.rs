@@ -0,0 +1,38 @@
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+
+/// Counts the number of ways to select between min and max items,
+/// where every item can be selected in the number of ways given by its weight.
+///
+/// The count is the sum of the elementary symmetric polynomials of degree min to max over the weights,
+/// which is calculated using dynamic programming.
+pub fn cardinality_count<'a>(weights: impl IntoIterator<Item = &'a BigUint>, min: usize, max: usize) -> BigUint {
+    let mut dp = vec![BigUint::from(1_u32)];
+
+    for weight in weights {
+        dp.push(BigUint::ZERO);
+        for k in (1..dp.len()).rev() {
+            let selected = &dp[k - 1] * weight;
+            dp[k] += selected;
+        }
+    }
+
+    dp.iter()
+        .skip(min)
+        .take((max + 1).saturating_sub(min))
+        .sum()
+}
+
+/// Calculates the base 10 logarithm of a count without overflowing.
+/// The logarithm of zero is negative infinity.
+pub fn log10(count: &BigUint) -> f64 {
+    if count.is_zero() {
+        return f64::NEG_INFINITY;
+    }
+
+    // Keep the 64 most significant bits, which is more than a f64 can represent anyway.
+    let shift = count.bits().saturating_sub(64);
+    let mantissa = (count >> shift).to_f64()
+        .expect("Count fits in 64 bits");
+    mantissa.log10() + shift as f64 * std::f64::consts::LOG10_2
+}
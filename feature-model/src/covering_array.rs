@@ -0,0 +1,251 @@
+use std::{collections::{BTreeMap, HashMap, HashSet}, fmt::Display};
+
+use rand::{Rng, seq::SliceRandom};
+
+use crate::{FeatureModel, bdd::NodeId, engine::Engine};
+
+/// The largest supported strength, since the number of interactions grows with the number of features to the power of t.
+pub const MAX_STRENGTH: usize = 3;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CoveringError {
+    #[error("The strength must be between 1 and {MAX_STRENGTH}, but was {0}")]
+    UnsupportedStrength(usize),
+}
+
+/// A set of valid configurations which covers every valid t-wise interaction of a feature model.
+pub struct CoveringArray {
+    /// The configurations, where the seed configurations come first.
+    /// Every configuration assigns a value to every feature of the model.
+    pub configurations: Vec<BTreeMap<String, bool>>,
+    /// The number of seed configurations which are valid, and are included in the array.
+    pub seeded: usize,
+    /// The number of seed configurations which are rejected by the model, and are left out.
+    pub rejected_seeds: usize,
+    pub coverage: Coverage,
+}
+
+/// How many of the valid t-wise interactions of a feature model are covered by a set of configurations.
+///
+/// An interaction is a selection or deselection of t different features,
+/// which is valid if some valid configuration contains it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coverage {
+    pub strength: usize,
+    pub valid_interactions: usize,
+    pub covered_interactions: usize,
+}
+
+impl Coverage {
+    /// The fraction of the valid interactions which are covered, which is 1 when there are no valid interactions.
+    pub fn ratio(&self) -> f64 {
+        if self.valid_interactions == 0 {
+            1.0
+        } else {
+            self.covered_interactions as f64 / self.valid_interactions as f64
+        }
+    }
+}
+
+/// A selection or deselection of t features, ordered by variable.
+type Interaction = Vec<(u32, bool)>;
+
+/// Generates a small set of valid configurations which covers every valid t-wise interaction
+/// between the concrete features of the model, for t from 1 to [MAX_STRENGTH].
+///
+/// The seed configurations are included first, so configurations used in the real world are always part of the array,
+/// and only the interactions they don't cover are covered by new configurations.
+/// Features of the model which a seed doesn't mention are completed with any valid values.
+/// New configurations are built greedily, by packing as many uncovered interactions into each configuration as the model allows,
+/// where the random number generator decides the order in which the interactions are packed.
+pub fn covering_array<R: Rng + ?Sized>(feature_model: &FeatureModel, strength: usize, seeds: &[BTreeMap<String, bool>], rng: &mut R) -> Result<CoveringArray, CoveringError> {
+    let mut engine = Engine::new(feature_model);
+    let interactions = interactions(&mut engine, feature_model, strength)?;
+    let valid_interactions = interactions.len();
+
+    let mut assignments = seeds.iter()
+        .filter_map(|seed| complete(&mut engine, seed))
+        .collect::<Vec<_>>();
+    let seeded = assignments.len();
+
+    let mut uncovered = interactions.into_iter()
+        .filter(|interaction| !assignments.iter().any(|assignment| covers(assignment, interaction)))
+        .collect::<Vec<_>>();
+    // Interactions are packed in a random order, since the enumeration order puts related interactions next to each other,
+    // but interactions which select more features are packed first, because they constrain the configuration the most.
+    uncovered.shuffle(rng);
+    uncovered.sort_by_key(|interaction| std::cmp::Reverse(interaction.iter().filter(|&&(_, value)| value).count()));
+
+    // Every uncovered interaction is added to the first partial configuration which can still be extended with it,
+    // or starts a new partial configuration if there is none.
+    let mut partial: Vec<(NodeId, Vec<Option<bool>>)> = vec![];
+    for interaction in uncovered {
+        let fits = partial.iter_mut().find_map(|(node, assigned)| {
+            let conflicts = interaction.iter().any(|&(variable, value)| assigned[variable as usize].is_some_and(|v| v != value));
+            if conflicts {
+                return None;
+            }
+
+            let restricted = interaction.iter()
+                .filter(|&&(variable, _)| assigned[variable as usize].is_none())
+                .fold(*node, |node, &(variable, value)| engine.bdd.restrict(node, variable, value));
+            (restricted != NodeId::FALSE).then_some((node, assigned, restricted))
+        });
+
+        let (node, assigned, restricted) = match fits {
+            Some(fit) => fit,
+            None => {
+                let restricted = interaction.iter().fold(engine.root, |node, &(variable, value)| engine.bdd.restrict(node, variable, value));
+                partial.push((engine.root, vec![None; engine.features().len()]));
+                let (node, assigned) = partial.last_mut().expect("Was just pushed");
+                (node, assigned, restricted)
+            }
+        };
+
+        *node = restricted;
+        for &(variable, value) in &interaction {
+            assigned[variable as usize] = Some(value);
+        }
+    }
+
+    for (node, assigned) in partial {
+        let mut assignment = engine.bdd.any_sat(node).expect("Partial configurations are only extended while they are valid");
+        for (value, assigned) in assignment.iter_mut().zip(assigned) {
+            *value = assigned.unwrap_or(*value);
+        }
+        assignments.push(assignment);
+    }
+
+    let configurations = assignments.into_iter()
+        .map(|assignment| engine.features().iter().cloned().zip(assignment).collect())
+        .collect();
+
+    Ok(CoveringArray {
+        configurations,
+        seeded,
+        rejected_seeds: seeds.len() - seeded,
+        coverage: Coverage { strength, valid_interactions, covered_interactions: valid_interactions },
+    })
+}
+
+/// Determines how many valid t-wise interactions of the model are covered by the configurations.
+/// An interaction is only covered by a configuration which assigns the same values to all of its features.
+pub fn coverage(feature_model: &FeatureModel, strength: usize, configurations: &[BTreeMap<String, bool>]) -> Result<Coverage, CoveringError> {
+    let mut engine = Engine::new(feature_model);
+    let interactions = interactions(&mut engine, feature_model, strength)?;
+
+    let covered_interactions = interactions.iter()
+        .filter(|interaction| configurations.iter().any(|configuration| interaction.iter()
+            .all(|&(variable, value)| configuration.get(&engine.features()[variable as usize]) == Some(&value))))
+        .count();
+
+    Ok(Coverage { strength, valid_interactions: interactions.len(), covered_interactions })
+}
+
+/// The distinct configurations ordered by how often they occur, most frequent first,
+/// where configurations which occur equally often keep their order.
+pub fn most_frequent(configurations: &[BTreeMap<String, bool>], count: usize) -> Vec<BTreeMap<String, bool>> {
+    let mut occurrences: HashMap<&BTreeMap<String, bool>, usize> = HashMap::new();
+    for configuration in configurations {
+        *occurrences.entry(configuration).or_default() += 1;
+    }
+
+    let mut seen = HashSet::new();
+    let mut distinct = configurations.iter()
+        .filter(|configuration| seen.insert(*configuration))
+        .collect::<Vec<_>>();
+    distinct.sort_by_key(|configuration| std::cmp::Reverse(occurrences[configuration]));
+
+    distinct.into_iter()
+        .take(count)
+        .cloned()
+        .collect()
+}
+
+/// Finds every valid interaction of t concrete features, excluding the root.
+///
+/// The interactions are enumerated by prefix, so the function restricted by a prefix
+/// is shared by all interactions which extend it, and invalid prefixes are pruned early.
+fn interactions(engine: &mut Engine, feature_model: &FeatureModel, strength: usize) -> Result<Vec<Interaction>, CoveringError> {
+    if !(1..=MAX_STRENGTH).contains(&strength) {
+        return Err(CoveringError::UnsupportedStrength(strength));
+    }
+
+    let excluded = feature_model.features()
+        .filter(|f| f.is_abstract)
+        .map(|f| f.name.as_str())
+        .chain(std::iter::once(feature_model.root_feature.name.as_str()))
+        .collect::<HashSet<_>>();
+    let variables = engine.features().iter()
+        .filter(|f| !excluded.contains(f.as_str()))
+        .map(|f| engine.variables[f])
+        .collect::<Vec<_>>();
+
+    let root = engine.root;
+    let mut interactions = vec![];
+    extend(engine, root, &variables, strength, &mut vec![], &mut interactions);
+    Ok(interactions)
+}
+
+fn extend(engine: &mut Engine, node: NodeId, variables: &[u32], strength: usize, prefix: &mut Interaction, interactions: &mut Vec<Interaction>) {
+    // The last feature of an interaction only needs to know which literals are satisfiable, which takes a single traversal.
+    if prefix.len() + 1 == strength {
+        let literals = engine.bdd.satisfiable_literals(node);
+        for &variable in variables {
+            for value in [false, true] {
+                if literals[variable as usize][value as usize] {
+                    prefix.push((variable, value));
+                    interactions.push(prefix.clone());
+                    prefix.pop();
+                }
+            }
+        }
+        return;
+    }
+
+    for (i, &variable) in variables.iter().enumerate() {
+        for value in [false, true] {
+            let restricted = engine.bdd.restrict(node, variable, value);
+            if restricted == NodeId::FALSE {
+                continue;
+            }
+
+            prefix.push((variable, value));
+            extend(engine, restricted, &variables[i + 1..], strength, prefix, interactions);
+            prefix.pop();
+        }
+    }
+}
+
+/// Completes a seed configuration into a valid assignment of every variable,
+/// or returns None if the seed is rejected by the model.
+fn complete(engine: &mut Engine, seed: &BTreeMap<String, bool>) -> Option<Vec<bool>> {
+    let mut node = engine.root;
+    let mut assigned = vec![];
+    for (feature, &enabled) in seed {
+        match engine.variables.get(feature) {
+            Some(&variable) => {
+                node = engine.bdd.restrict(node, variable, enabled);
+                assigned.push((variable, enabled));
+            }
+            None if enabled => return None,
+            None => continue,
+        }
+    }
+
+    let mut assignment = engine.bdd.any_sat(node)?;
+    for (variable, value) in assigned {
+        assignment[variable as usize] = value;
+    }
+    Some(assignment)
+}
+
+fn covers(assignment: &[bool], interaction: &Interaction) -> bool {
+    interaction.iter().all(|&(variable, value)| assignment[variable as usize] == value)
+}
+
+impl Display for Coverage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} of {} valid {}-wise interactions ({:.1}%)", self.covered_interactions, self.valid_interactions, self.strength, self.ratio() * 100.0)
+    }
+}
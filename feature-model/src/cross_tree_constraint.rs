@@ -1,32 +1,176 @@
-use std::fmt::Display;
+use std::{collections::BTreeSet, fmt::Display};
 
+use serde::{Deserialize, Serialize};
+
+/// A propositional formula over features.
+///
+/// [And](CrossTreeConstraint::And) and [Or](CrossTreeConstraint::Or) are n-ary,
+/// where an empty conjunction is true and an empty disjunction is false.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum CrossTreeConstraint {
     Feature(String),
-    And(Box<CrossTreeConstraint>, Box<CrossTreeConstraint>),
-    Or(Box<CrossTreeConstraint>, Box<CrossTreeConstraint>),
+    And(Vec<CrossTreeConstraint>),
+    Or(Vec<CrossTreeConstraint>),
     Implies(Box<CrossTreeConstraint>, Box<CrossTreeConstraint>),
+    Equiv(Box<CrossTreeConstraint>, Box<CrossTreeConstraint>),
     Not(Box<CrossTreeConstraint>),
 }
 
+impl CrossTreeConstraint {
+    /// Returns the names of all features referenced in the constraint.
+    pub fn features(&self) -> Vec<&str> {
+        let mut features = vec![];
+        let mut stack = vec![self];
+        while let Some(constraint) = stack.pop() {
+            match constraint {
+                CrossTreeConstraint::Feature(name) => features.push(name.as_str()),
+                CrossTreeConstraint::And(operands)
+                | CrossTreeConstraint::Or(operands) => stack.extend(operands.iter().rev()),
+                CrossTreeConstraint::Implies(a, b)
+                | CrossTreeConstraint::Equiv(a, b) => {
+                    stack.push(b);
+                    stack.push(a);
+                }
+                CrossTreeConstraint::Not(a) => stack.push(a),
+            }
+        }
+        features
+    }
+
+    /// Converts the constraint to negation normal form,
+    /// where negations are only applied to features,
+    /// and the only other operators are [And](CrossTreeConstraint::And) and [Or](CrossTreeConstraint::Or).
+    ///
+    /// The result is simplified by flattening nested operators, removing duplicate operands
+    /// and folding operators which contain both a feature and its negation into constants.
+    pub fn to_nnf(&self) -> CrossTreeConstraint {
+        self.nnf(true)
+    }
+
+    /// Converts the constraint to conjunctive normal form,
+    /// a conjunction of clauses, where every clause is a disjunction of features or negated features.
+    ///
+    /// Clauses with a feature and its negation are removed,
+    /// along with duplicate features in a clause and duplicate clauses.
+    /// The conversion distributes disjunctions over conjunctions,
+    /// so the result can grow exponentially in the size of the constraint.
+    pub fn to_cnf(&self) -> CrossTreeConstraint {
+        let clauses = Self::clauses(&self.to_nnf());
+        let clauses = clauses.into_iter()
+            .map(|clause| {
+                let literals = clause.into_iter()
+                    .map(|(name, positive)| literal(name, positive))
+                    .collect::<Vec<_>>();
+                match <[_; 1]>::try_from(literals) {
+                    Ok([literal]) => literal,
+                    Err(literals) => CrossTreeConstraint::Or(literals),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        match <[_; 1]>::try_from(clauses) {
+            Ok([clause]) => clause,
+            Err(clauses) => CrossTreeConstraint::And(clauses),
+        }
+    }
+
+    /// The negation normal form of the constraint if positive is true, otherwise of its negation.
+    fn nnf(&self, positive: bool) -> CrossTreeConstraint {
+        match (self, positive) {
+            (CrossTreeConstraint::Feature(name), _) => literal(name.clone(), positive),
+            (CrossTreeConstraint::Not(a), _) => a.nnf(!positive),
+            (CrossTreeConstraint::And(operands), true) => simplified_and(operands.iter().map(|c| c.nnf(true))),
+            (CrossTreeConstraint::And(operands), false) => simplified_or(operands.iter().map(|c| c.nnf(false))),
+            (CrossTreeConstraint::Or(operands), true) => simplified_or(operands.iter().map(|c| c.nnf(true))),
+            (CrossTreeConstraint::Or(operands), false) => simplified_and(operands.iter().map(|c| c.nnf(false))),
+            (CrossTreeConstraint::Implies(a, b), true) => simplified_or([a.nnf(false), b.nnf(true)]),
+            (CrossTreeConstraint::Implies(a, b), false) => simplified_and([a.nnf(true), b.nnf(false)]),
+            (CrossTreeConstraint::Equiv(a, b), _) => {
+                let a_implies_b = simplified_or([a.nnf(false), b.nnf(positive)]);
+                let b_implies_a = simplified_or([a.nnf(true), b.nnf(!positive)]);
+                simplified_and([a_implies_b, b_implies_a])
+            }
+        }
+    }
+
+    /// The clauses of a constraint in negation normal form.
+    fn clauses(nnf: &CrossTreeConstraint) -> BTreeSet<BTreeSet<(String, bool)>> {
+        match nnf {
+            CrossTreeConstraint::Feature(name) => BTreeSet::from([BTreeSet::from([(name.clone(), true)])]),
+            CrossTreeConstraint::Not(a) => match a.as_ref() {
+                CrossTreeConstraint::Feature(name) => BTreeSet::from([BTreeSet::from([(name.clone(), false)])]),
+                _ => unreachable!("Negations in negation normal form only apply to features"),
+            },
+            CrossTreeConstraint::And(operands) => operands.iter()
+                .flat_map(Self::clauses)
+                .collect(),
+            CrossTreeConstraint::Or(operands) => operands.iter()
+                .map(Self::clauses)
+                .fold(BTreeSet::from([BTreeSet::new()]), |clauses, operand| {
+                    clauses.iter()
+                        .flat_map(|clause| operand.iter().map(move |other| clause | other))
+                        .filter(|clause| !clause.iter().any(|(name, positive)| clause.contains(&(name.clone(), !positive))))
+                        .collect()
+                }),
+            CrossTreeConstraint::Implies(..) | CrossTreeConstraint::Equiv(..) => {
+                unreachable!("Negation normal form only contains conjunctions, disjunctions and negations")
+            }
+        }
+    }
+
+    /// The binding strength of the operator at the top of the constraint,
+    /// following the precedence of UVL where <=> binds the weakest and ! the strongest.
+    fn precedence(&self) -> u8 {
+        match self {
+            CrossTreeConstraint::Equiv(..) => 0,
+            CrossTreeConstraint::Implies(..) => 1,
+            CrossTreeConstraint::Or(operands) | CrossTreeConstraint::And(operands) if operands.is_empty() => 5,
+            CrossTreeConstraint::Or(..) => 2,
+            CrossTreeConstraint::And(..) => 3,
+            CrossTreeConstraint::Not(..) => 4,
+            CrossTreeConstraint::Feature(..) => 5,
+        }
+    }
+}
+
 pub fn implies(a: impl Into<CrossTreeConstraint>, b: impl Into<CrossTreeConstraint>) -> CrossTreeConstraint {
     CrossTreeConstraint::Implies(
-        Box::new(a.into()), 
-        Box::new(b.into()), 
+        Box::new(a.into()),
+        Box::new(b.into()),
     )
 }
 
+/// Conjunction of two constraints, where nested conjunctions are flattened.
 pub fn and(a: impl Into<CrossTreeConstraint>, b: impl Into<CrossTreeConstraint>) -> CrossTreeConstraint {
-    CrossTreeConstraint::And(
-        Box::new(a.into()), 
-        Box::new(b.into()), 
-    )
+    conjunction([a.into(), b.into()])
 }
 
+/// Disjunction of two constraints, where nested disjunctions are flattened.
 pub fn or(a: impl Into<CrossTreeConstraint>, b: impl Into<CrossTreeConstraint>) -> CrossTreeConstraint {
-    CrossTreeConstraint::Or(
-        Box::new(a.into()), 
-        Box::new(b.into()), 
-    )
+    disjunction([a.into(), b.into()])
+}
+
+/// Conjunction of any number of constraints, where nested conjunctions are flattened.
+pub fn conjunction<C: Into<CrossTreeConstraint>>(operands: impl IntoIterator<Item = C>) -> CrossTreeConstraint {
+    let operands = operands.into_iter()
+        .flat_map(|operand| match operand.into() {
+            CrossTreeConstraint::And(operands) => operands,
+            operand => vec![operand],
+        })
+        .collect();
+    CrossTreeConstraint::And(operands)
+}
+
+/// Disjunction of any number of constraints, where nested disjunctions are flattened.
+pub fn disjunction<C: Into<CrossTreeConstraint>>(operands: impl IntoIterator<Item = C>) -> CrossTreeConstraint {
+    let operands = operands.into_iter()
+        .flat_map(|operand| match operand.into() {
+            CrossTreeConstraint::Or(operands) => operands,
+            operand => vec![operand],
+        })
+        .collect();
+    CrossTreeConstraint::Or(operands)
 }
 
 pub fn not(constraint: impl Into<CrossTreeConstraint>) -> CrossTreeConstraint {
@@ -37,6 +181,62 @@ pub fn exclusive(a: impl Into<CrossTreeConstraint>, b: impl Into<CrossTreeConstr
     or(not(a.into()), not(b.into()))
 }
 
+pub fn equivalent(a: impl Into<CrossTreeConstraint>, b: impl Into<CrossTreeConstraint>) -> CrossTreeConstraint {
+    CrossTreeConstraint::Equiv(
+        Box::new(a.into()),
+        Box::new(b.into()),
+    )
+}
+
+fn literal(name: String, positive: bool) -> CrossTreeConstraint {
+    if positive {
+        CrossTreeConstraint::Feature(name)
+    } else {
+        not(name)
+    }
+}
+
+/// Builds a conjunction of constraints in negation normal form.
+/// A conjunction containing false, or a feature and its negation, becomes false.
+fn simplified_and(operands: impl IntoIterator<Item = CrossTreeConstraint>) -> CrossTreeConstraint {
+    simplified(operands, true)
+}
+
+/// Builds a disjunction of constraints in negation normal form.
+/// A disjunction containing true, or a feature and its negation, becomes true.
+fn simplified_or(operands: impl IntoIterator<Item = CrossTreeConstraint>) -> CrossTreeConstraint {
+    simplified(operands, false)
+}
+
+fn simplified(operands: impl IntoIterator<Item = CrossTreeConstraint>, is_and: bool) -> CrossTreeConstraint {
+    let operator = |operands| if is_and { CrossTreeConstraint::And(operands) } else { CrossTreeConstraint::Or(operands) };
+    let dual = |operands| if is_and { CrossTreeConstraint::Or(operands) } else { CrossTreeConstraint::And(operands) };
+    let absorbing = dual(vec![]);
+
+    let mut result: Vec<CrossTreeConstraint> = vec![];
+    for operand in operands {
+        let nested = match operand {
+            CrossTreeConstraint::And(operands) if is_and => operands,
+            CrossTreeConstraint::Or(operands) if !is_and => operands,
+            operand => vec![operand],
+        };
+
+        for operand in nested {
+            if operand == absorbing || result.contains(&not(operand.clone()).nnf(true)) {
+                return absorbing;
+            }
+            if !result.contains(&operand) {
+                result.push(operand);
+            }
+        }
+    }
+
+    match <[_; 1]>::try_from(result) {
+        Ok([operand]) => operand,
+        Err(operands) => operator(operands),
+    }
+}
+
 impl From<String> for CrossTreeConstraint {
     fn from(value: String) -> Self {
         CrossTreeConstraint::Feature(value)
@@ -49,14 +249,48 @@ impl From<&str> for CrossTreeConstraint {
     }
 }
 
+/// Writes the constraint in UVL syntax with the minimal number of parentheses.
+///
+/// Binary operators are left-associative, so the right operand is parenthesized
+/// if it has the same precedence as the operator.
+/// An empty conjunction and disjunction are written as the constants `true` and `false`.
 impl Display for CrossTreeConstraint {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let precedence = self.precedence();
+        let operand = |f: &mut std::fmt::Formatter<'_>, operand: &CrossTreeConstraint, parenthesize: bool| {
+            if parenthesize {
+                write!(f, "({operand})")
+            } else {
+                write!(f, "{operand}")
+            }
+        };
+        let binary = |f: &mut std::fmt::Formatter<'_>, a: &CrossTreeConstraint, b: &CrossTreeConstraint, symbol: &str| {
+            operand(f, a, a.precedence() < precedence)?;
+            write!(f, " {symbol} ")?;
+            operand(f, b, b.precedence() <= precedence)
+        };
+        let n_ary = |f: &mut std::fmt::Formatter<'_>, operands: &[CrossTreeConstraint], symbol: &str| {
+            for (i, o) in operands.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {symbol} ")?;
+                }
+                operand(f, o, o.precedence() <= precedence)?;
+            }
+            Ok(())
+        };
+
         match self {
             CrossTreeConstraint::Feature(name) => write!(f, "\"{name}\""),
-            CrossTreeConstraint::And(a, b) => write!(f, "{a} & {b}"),
-            CrossTreeConstraint::Or(a, b) => write!(f, "{a} | {b}"),
-            CrossTreeConstraint::Implies(a, b) => write!(f, "{a} => {b}"),
-            CrossTreeConstraint::Not(constraint) => write!(f, "!{constraint}"),
+            CrossTreeConstraint::And(operands) if operands.is_empty() => write!(f, "true"),
+            CrossTreeConstraint::Or(operands) if operands.is_empty() => write!(f, "false"),
+            CrossTreeConstraint::And(operands) => n_ary(f, operands, "&"),
+            CrossTreeConstraint::Or(operands) => n_ary(f, operands, "|"),
+            CrossTreeConstraint::Implies(a, b) => binary(f, a, b, "=>"),
+            CrossTreeConstraint::Equiv(a, b) => binary(f, a, b, "<=>"),
+            CrossTreeConstraint::Not(constraint) => {
+                write!(f, "!")?;
+                operand(f, constraint, constraint.precedence() < precedence)
+            }
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,26 @@
+use std::{collections::{BTreeMap, HashSet}, io::Write};
+
+use crate::FeatureModel;
+
+/// Writes a configuration in the .csvconf format of the configuration scraper,
+/// with one `"feature",True` or `"feature",False` line per feature.
+///
+/// The root feature and abstract features are left out,
+/// because they don't correspond to features of the crate.
+pub fn write<W: Write>(writer: &mut W, feature_model: &FeatureModel, configuration: &BTreeMap<String, bool>) -> std::io::Result<()> {
+    let excluded = feature_model.features()
+        .filter(|f| f.is_abstract)
+        .map(|f| f.name.as_str())
+        .chain(std::iter::once(feature_model.root_feature.name.as_str()))
+        .collect::<HashSet<_>>();
+
+    for (feature, &enabled) in configuration {
+        if excluded.contains(feature.as_str()) {
+            continue;
+        }
+
+        writeln!(writer, "\"{feature}\",{}", if enabled { "True" } else { "False" })?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,248 @@
+use std::{collections::{HashMap, HashSet}, fmt::Display};
+
+use num_bigint::BigUint;
+
+use crate::{FeatureModel, bdd::NodeId, cross_tree_constraint::CrossTreeConstraint, engine::Engine, group::GroupKind};
+
+/// The differences between an old and a new version of a feature model.
+pub struct Diff {
+    /// Features of the new model which don't exist in the old model.
+    pub added_features: Vec<String>,
+    /// Features of the old model which don't exist in the new model.
+    pub removed_features: Vec<String>,
+    /// Features which exist in both models, but have different parents.
+    pub moved_features: Vec<MovedFeature>,
+    /// Features which have the same parent in both models, but belong to a different kind of group.
+    pub group_changes: Vec<GroupChange>,
+    /// Constraints of the new model which don't exist in the old model.
+    pub added_constraints: Vec<CrossTreeConstraint>,
+    /// Constraints of the old model which don't exist in the new model.
+    pub removed_constraints: Vec<CrossTreeConstraint>,
+    pub semantic: SemanticDiff,
+}
+
+pub struct MovedFeature {
+    pub name: String,
+    pub old_parent: String,
+    pub new_parent: String,
+}
+
+pub struct GroupChange {
+    pub name: String,
+    pub old_kind: GroupKind,
+    pub new_kind: GroupKind,
+}
+
+/// Compares the configurations of two feature models.
+///
+/// Configurations are compared over the concrete features of both models.
+/// The abstract features of each model are projected out, and a concrete feature of one model
+/// which isn't a concrete feature of the other is deselected in all configurations of the other.
+pub struct SemanticDiff {
+    pub edit: Edit,
+    /// The number of configurations which are only valid in the old model.
+    pub removed_configurations: BigUint,
+    /// The number of configurations which are only valid in the new model.
+    pub added_configurations: BigUint,
+    /// The selected features of a configuration which is only valid in the old model.
+    pub removed_example: Option<Vec<String>>,
+    /// The selected features of a configuration which is only valid in the new model.
+    pub added_example: Option<Vec<String>>,
+}
+
+/// Classification of an edit by how it changes the set of valid configurations,
+/// as described by Thüm et al. in "Reasoning about Edits to Feature Models".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    /// Both models have the same configurations.
+    Refactoring,
+    /// The new model has a subset of the configurations of the old model.
+    Specialization,
+    /// The new model has a superset of the configurations of the old model.
+    Generalization,
+    /// Both models have configurations which are invalid in the other model.
+    ArbitraryEdit,
+}
+
+/// Finds the structural and semantic differences between two feature models.
+pub fn diff(old: &FeatureModel, new: &FeatureModel) -> Diff {
+    let old_parents = parents(old);
+    let new_parents = parents(new);
+
+    let added_features = new.features()
+        .filter(|f| !old_parents.contains_key(f.name.as_str()) && f.name != old.root_feature.name)
+        .map(|f| f.name.clone())
+        .collect::<Vec<_>>();
+    let removed_features = old.features()
+        .filter(|f| !new_parents.contains_key(f.name.as_str()) && f.name != new.root_feature.name)
+        .map(|f| f.name.clone())
+        .collect::<Vec<_>>();
+
+    let mut moved_features = vec![];
+    let mut group_changes = vec![];
+    for (name, &(old_parent, old_kind)) in &old_parents {
+        let Some(&(new_parent, new_kind)) = new_parents.get(name) else {
+            continue;
+        };
+
+        if old_parent != new_parent {
+            moved_features.push(MovedFeature { name: name.to_string(), old_parent: old_parent.to_owned(), new_parent: new_parent.to_owned() });
+        } else if old_kind != new_kind {
+            group_changes.push(GroupChange { name: name.to_string(), old_kind, new_kind });
+        }
+    }
+    moved_features.sort_by(|a, b| a.name.cmp(&b.name));
+    group_changes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let added_constraints = constraint_difference(&new.cross_tree_constraints, &old.cross_tree_constraints);
+    let removed_constraints = constraint_difference(&old.cross_tree_constraints, &new.cross_tree_constraints);
+
+    Diff {
+        added_features,
+        removed_features,
+        moved_features,
+        group_changes,
+        added_constraints,
+        removed_constraints,
+        semantic: semantic_diff(old, new),
+    }
+}
+
+/// Compares the configurations of two feature models by compiling both into the same [crate::bdd::Bdd].
+pub fn semantic_diff(old: &FeatureModel, new: &FeatureModel) -> SemanticDiff {
+    let mut engine = Engine::new(old);
+    let old_root = engine.root;
+    let new_root = engine.compile(new);
+    let old_root = project_concrete(&mut engine, old_root, old);
+    let new_root = project_concrete(&mut engine, new_root, new);
+
+    let not_new = engine.bdd.not(new_root);
+    let not_old = engine.bdd.not(old_root);
+    let removed = engine.bdd.and(old_root, not_new);
+    let added = engine.bdd.and(new_root, not_old);
+
+    let edit = match (removed == NodeId::FALSE, added == NodeId::FALSE) {
+        (true, true) => Edit::Refactoring,
+        (false, true) => Edit::Specialization,
+        (true, false) => Edit::Generalization,
+        (false, false) => Edit::ArbitraryEdit,
+    };
+
+    let example = |f: NodeId| engine.bdd.any_sat(f)
+        .map(|assignment| {
+            engine.features().iter()
+                .zip(assignment)
+                .filter(|(_, selected)| *selected)
+                .map(|(name, _)| name.clone())
+                .collect()
+        });
+
+    SemanticDiff {
+        edit,
+        removed_configurations: engine.bdd.count(removed),
+        added_configurations: engine.bdd.count(added),
+        removed_example: example(removed),
+        added_example: example(added),
+    }
+}
+
+/// Maps every feature, except the root, to the name of its parent and the kind of its group.
+fn parents(feature_model: &FeatureModel) -> HashMap<&str, (&str, GroupKind)> {
+    feature_model.features()
+        .flat_map(|parent| parent.groups.iter()
+            .flat_map(move |group| group.features.iter()
+                .map(move |child| (child.name.as_str(), (parent.name.as_str(), group.kind())))))
+        .collect()
+}
+
+/// The constraints of a which aren't in b, where duplicates are counted.
+fn constraint_difference(a: &[CrossTreeConstraint], b: &[CrossTreeConstraint]) -> Vec<CrossTreeConstraint> {
+    let mut remaining = b.iter().collect::<Vec<_>>();
+    a.iter()
+        .filter(|constraint| match remaining.iter().position(|other| other == constraint) {
+            Some(i) => {
+                remaining.swap_remove(i);
+                false
+            }
+            None => true,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Projects the function of a model onto its concrete features, by existentially quantifying its abstract features,
+/// and deselects every feature which isn't a concrete feature of the model.
+fn project_concrete(engine: &mut Engine, root: NodeId, feature_model: &FeatureModel) -> NodeId {
+    let abstract_features = feature_model.features()
+        .filter(|f| f.is_abstract)
+        .map(|f| f.name.as_str())
+        .collect::<HashSet<_>>();
+    let concrete = feature_model.features()
+        .map(|f| f.name.as_str())
+        .chain(feature_model.cross_tree_constraints.iter().flat_map(|c| c.features()))
+        .filter(|f| !abstract_features.contains(f))
+        .collect::<HashSet<_>>();
+
+    let abstract_variables = abstract_features.iter()
+        .map(|f| engine.variables[*f])
+        .collect::<HashSet<_>>();
+    let projected = engine.bdd.exists(root, |v| abstract_variables.contains(&v));
+
+    let deselected = engine.features().iter()
+        .filter(|name| !concrete.contains(name.as_str()))
+        .map(|name| engine.variables[name])
+        .collect::<Vec<_>>();
+
+    deselected.into_iter().fold(projected, |root, variable| {
+        let deselected = engine.bdd.literal(variable, false);
+        engine.bdd.and(root, deselected)
+    })
+}
+
+impl Display for Edit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Edit::Refactoring => write!(f, "refactoring"),
+            Edit::Specialization => write!(f, "specialization"),
+            Edit::Generalization => write!(f, "generalization"),
+            Edit::ArbitraryEdit => write!(f, "arbitrary edit"),
+        }
+    }
+}
+
+/// Writes a report of the differences, with one line per difference.
+impl Display for Diff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for name in &self.added_features {
+            writeln!(f, "+ feature \"{name}\"")?;
+        }
+        for name in &self.removed_features {
+            writeln!(f, "- feature \"{name}\"")?;
+        }
+        for moved in &self.moved_features {
+            writeln!(f, "~ feature \"{}\" moved from \"{}\" to \"{}\"", moved.name, moved.old_parent, moved.new_parent)?;
+        }
+        for change in &self.group_changes {
+            writeln!(f, "~ feature \"{}\" changed group from {} to {}", change.name, change.old_kind, change.new_kind)?;
+        }
+        for constraint in &self.added_constraints {
+            writeln!(f, "+ constraint {constraint}")?;
+        }
+        for constraint in &self.removed_constraints {
+            writeln!(f, "- constraint {constraint}")?;
+        }
+
+        let semantic = &self.semantic;
+        writeln!(f, "Edit: {}", semantic.edit)?;
+        writeln!(f, "Configurations only in the old model: {}", semantic.removed_configurations)?;
+        writeln!(f, "Configurations only in the new model: {}", semantic.added_configurations)?;
+        if let Some(example) = &semantic.removed_example {
+            writeln!(f, "Example only in the old model: {}", example.join(", "))?;
+        }
+        if let Some(example) = &semantic.added_example {
+            writeln!(f, "Example only in the new model: {}", example.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+use std::{collections::HashMap, io::Write};
+
+use crate::{FeatureModel, cross_tree_constraint::CrossTreeConstraint, feature::Feature, group::Group};
+
+/// A literal in DIMACS notation, a positive or negative variable index starting from 1.
+type Literal = i64;
+
+/// Writes the feature model as a formula in conjunctive normal form in the DIMACS format.
+///
+/// Every feature gets a variable, which is listed in a `c <id> <name>` comment.
+/// Group cardinalities and cross-tree constraints introduce auxiliary variables,
+/// which are fully determined by the features,
+/// so the number of models of the formula equals the number of configurations of the feature model.
+pub fn write<W: Write>(writer: &mut W, feature_model: &FeatureModel) -> std::io::Result<()> {
+    let encoding = Encoding::new(feature_model);
+
+    for (i, name) in encoding.features.iter().enumerate() {
+        writeln!(writer, "c {} {}", i + 1, name)?;
+    }
+
+    writeln!(writer, "p cnf {} {}", encoding.variable_count, encoding.clauses.len())?;
+    for clause in &encoding.clauses {
+        for literal in clause {
+            write!(writer, "{literal} ")?;
+        }
+        writeln!(writer, "0")?;
+    }
+
+    Ok(())
+}
+
+/// Encodes a feature model as a set of clauses.
+///
+/// Features are numbered by a pre-order traversal of the tree,
+/// followed by features which are only mentioned in cross-tree constraints.
+/// Auxiliary variables are numbered after all features.
+struct Encoding {
+    features: Vec<String>,
+    variables: HashMap<String, Literal>,
+    variable_count: Literal,
+    clauses: Vec<Vec<Literal>>,
+}
+
+impl Encoding {
+    fn new(feature_model: &FeatureModel) -> Encoding {
+        let mut encoding = Encoding {
+            features: vec![],
+            variables: HashMap::new(),
+            variable_count: 0,
+            clauses: vec![],
+        };
+
+        for feature in feature_model.root_feature.iter() {
+            encoding.variable_for(&feature.name);
+        }
+        for name in feature_model.cross_tree_constraints.iter().flat_map(|c| c.features()) {
+            encoding.variable_for(name);
+        }
+
+        let root = encoding.variables[&feature_model.root_feature.name];
+        encoding.clauses.push(vec![root]);
+        encoding.encode_feature(&feature_model.root_feature);
+
+        for constraint in &feature_model.cross_tree_constraints {
+            encoding.encode_constraint(constraint);
+        }
+
+        encoding
+    }
+
+    fn variable_for(&mut self, name: &str) -> Literal {
+        if let Some(&variable) = self.variables.get(name) {
+            return variable;
+        }
+
+        let variable = self.new_variable();
+        self.features.push(name.to_owned());
+        self.variables.insert(name.to_owned(), variable);
+        variable
+    }
+
+    fn new_variable(&mut self) -> Literal {
+        self.variable_count += 1;
+        self.variable_count
+    }
+
+    /// Encodes the tree relations below a feature.
+    ///
+    /// Every child implies its parent,
+    /// and a selected parent selects a number of children within the cardinality of each group.
+    fn encode_feature(&mut self, feature: &Feature) {
+        let parent = self.variables[&feature.name];
+        for group in &feature.groups {
+            let children = group.features.iter()
+                .map(|child| self.variables[&child.name])
+                .collect::<Vec<_>>();
+
+            for &child in &children {
+                self.clauses.push(vec![-child, parent]);
+            }
+
+            self.encode_cardinality(parent, &children, group);
+
+            for child in &group.features {
+                self.encode_feature(child);
+            }
+        }
+    }
+
+    /// Encodes that a selected parent selects between min and max of the children.
+    ///
+    /// Mandatory features and groups with a lower bound of one, as well as groups with an upper bound of one,
+    /// are encoded directly. Other bounds are encoded with a sequential counter.
+    fn encode_cardinality(&mut self, parent: Literal, children: &[Literal], group: &Group) {
+        let n = children.len();
+        let (min, max) = (group.min, group.max.min(n));
+
+        if min == n {
+            for &child in children {
+                self.clauses.push(vec![-parent, child]);
+            }
+            return;
+        }
+
+        if min > max {
+            self.clauses.push(vec![-parent]);
+            return;
+        }
+
+        let needs_counter = min > 1 || (1 < max && max < n);
+        let at_least = needs_counter.then(|| self.sequential_counter(children));
+
+        match min {
+            0 => {}
+            1 => self.clauses.push(std::iter::once(-parent).chain(children.iter().copied()).collect()),
+            _ => self.clauses.push(vec![-parent, at_least.as_ref().expect("Counter is created when min > 1")[min - 1]]),
+        }
+
+        if max == 0 {
+            self.clauses.extend(children.iter().map(|&child| vec![-parent, -child]));
+        } else if max == 1 {
+            for (i, &a) in children.iter().enumerate() {
+                for &b in &children[i + 1..] {
+                    self.clauses.push(vec![-parent, -a, -b]);
+                }
+            }
+        } else if max < n {
+            self.clauses.push(vec![-parent, -at_least.as_ref().expect("Counter is created when 1 < max < n")[max]]);
+        }
+    }
+
+    /// Creates variables where the k'th variable is true if and only if at least k + 1 of the literals are true.
+    ///
+    /// The counter for the first i literals is defined from the counter for the first i - 1 literals:
+    /// at least k of the first i literals are true if at least k of the first i - 1 literals are true,
+    /// or if the i'th literal and at least k - 1 of the first i - 1 literals are true.
+    fn sequential_counter(&mut self, literals: &[Literal]) -> Vec<Literal> {
+        let mut at_least: Vec<Literal> = vec![];
+        for &literal in literals {
+            let mut next = vec![];
+            for k in 0..=at_least.len() {
+                let with = if k == 0 { literal } else { self.and(&[at_least[k - 1], literal]) };
+                let counter = match at_least.get(k) {
+                    Some(&without) => self.or(&[without, with]),
+                    None => with,
+                };
+                next.push(counter);
+            }
+            at_least = next;
+        }
+        at_least
+    }
+
+    /// Encodes the constraint with the Tseitin transformation,
+    /// where every operator gets an auxiliary variable that is equivalent to its operands.
+    fn encode_constraint(&mut self, constraint: &CrossTreeConstraint) {
+        match constraint {
+            CrossTreeConstraint::And(operands) => {
+                for operand in operands {
+                    self.encode_constraint(operand);
+                }
+            }
+            constraint => {
+                let literal = self.tseitin(constraint);
+                self.clauses.push(vec![literal]);
+            }
+        }
+    }
+
+    fn tseitin(&mut self, constraint: &CrossTreeConstraint) -> Literal {
+        match constraint {
+            CrossTreeConstraint::Feature(name) => self.variable_for(name),
+            CrossTreeConstraint::Not(a) => -self.tseitin(a),
+            CrossTreeConstraint::And(operands) => {
+                let operands = operands.iter().map(|o| self.tseitin(o)).collect::<Vec<_>>();
+                self.and(&operands)
+            }
+            CrossTreeConstraint::Or(operands) => {
+                let operands = operands.iter().map(|o| self.tseitin(o)).collect::<Vec<_>>();
+                self.or(&operands)
+            }
+            CrossTreeConstraint::Implies(a, b) => {
+                let a = self.tseitin(a);
+                let b = self.tseitin(b);
+                self.or(&[-a, b])
+            }
+            CrossTreeConstraint::Equiv(a, b) => {
+                let a = self.tseitin(a);
+                let b = self.tseitin(b);
+                let result = self.new_variable();
+                self.clauses.push(vec![-result, -a, b]);
+                self.clauses.push(vec![-result, a, -b]);
+                self.clauses.push(vec![result, a, b]);
+                self.clauses.push(vec![result, -a, -b]);
+                result
+            }
+        }
+    }
+
+    /// Creates a variable which is equivalent to the conjunction of the literals.
+    fn and(&mut self, literals: &[Literal]) -> Literal {
+        -self.or(&literals.iter().map(|&l| -l).collect::<Vec<_>>())
+    }
+
+    /// Creates a variable which is equivalent to the disjunction of the literals.
+    fn or(&mut self, literals: &[Literal]) -> Literal {
+        let result = self.new_variable();
+        self.clauses.push(std::iter::once(-result).chain(literals.iter().copied()).collect());
+        for &literal in literals {
+            self.clauses.push(vec![result, -literal]);
+        }
+        result
+    }
+}
@@ -0,0 +1,241 @@
+use crate::{FeatureModel, attribute::Attribute, cross_tree_constraint::CrossTreeConstraint, feature::Feature, group::Group};
+
+/// Error returned when an edit would leave the feature model in an invalid state.
+/// The model is left unchanged when an edit fails.
+#[derive(Debug, thiserror::Error)]
+pub enum EditError {
+    #[error("Feature {0} does not exist")]
+    UnknownFeature(String),
+    #[error("Feature {0} already exists")]
+    DuplicateFeature(String),
+    #[error("Feature {0} has no group with index {1}")]
+    UnknownGroup(String, usize),
+    #[error("The root feature can't be removed or moved")]
+    RootFeature,
+    #[error("Feature {0} can't be moved into its own subtree")]
+    Cycle(String),
+    #[error("Invalid cardinality [{0}..{1}] for a group with {2} features")]
+    InvalidCardinality(usize, usize, usize),
+    #[error("Feature {0} is still referenced by a cross-tree constraint")]
+    ReferencedByConstraint(String),
+    #[error("No cross-tree constraint with index {0}")]
+    UnknownConstraint(usize),
+    #[error("Attribute {0} must be a finite number")]
+    NonFiniteAttribute(String),
+}
+
+type Result<T> = std::result::Result<T, EditError>;
+
+/// Editing API of the feature model.
+/// 
+/// Features are identified by their name.
+/// Every edit keeps the names of features unique, keeps cross-tree constraints referring to existing features,
+/// and updates the cached configuration counts of all affected features and groups.
+impl FeatureModel {
+    /// Finds the feature with the given name.
+    pub fn find_feature(&self, name: &str) -> Option<&Feature> {
+        self.root_feature.find(name)
+    }
+
+    /// Iterates over all features in pre-order.
+    pub fn features(&self) -> impl Iterator<Item = &Feature> {
+        self.root_feature.iter()
+    }
+
+    /// Adds a feature and its subtree to a group of the parent feature.
+    /// See [Group] for how the cardinality of the group is updated.
+    pub fn add_feature(&mut self, parent: &str, group: usize, feature: Feature) -> Result<()> {
+        if let Some(duplicate) = feature.iter().find(|f| self.find_feature(&f.name).is_some()) {
+            return Err(EditError::DuplicateFeature(duplicate.name.clone()));
+        }
+
+        self.check_group(parent, group)?;
+        self.edit_feature(parent, |parent| parent.groups[group].push(feature))
+    }
+
+    /// Adds a group to the parent feature, and returns the index of the new group.
+    pub fn add_group(&mut self, parent: &str, group: Group) -> Result<usize> {
+        if let Some(duplicate) = group.features.iter()
+            .flat_map(|f| f.iter())
+            .find(|f| self.find_feature(&f.name).is_some())
+        {
+            return Err(EditError::DuplicateFeature(duplicate.name.clone()));
+        }
+
+        self.edit_feature(parent, |parent| {
+            parent.groups.push(group);
+            parent.groups.len() - 1
+        })
+    }
+
+    /// Removes a feature and its subtree from the model.
+    /// 
+    /// The features in the subtree must not be referenced by any cross-tree constraint.
+    /// A group is removed along with its last feature.
+    pub fn remove_feature(&mut self, name: &str) -> Result<Feature> {
+        let feature = self.find_feature(name)
+            .ok_or_else(|| EditError::UnknownFeature(name.to_owned()))?;
+        if let Some(referenced) = feature.iter().find(|f| self.is_referenced(&f.name)) {
+            return Err(EditError::ReferencedByConstraint(referenced.name.clone()));
+        }
+
+        self.detach_feature(name, true)
+    }
+
+    /// Moves a feature and its subtree into a group of another feature.
+    pub fn move_feature(&mut self, name: &str, new_parent: &str, group: usize) -> Result<()> {
+        let mut path = self.root_feature.path_to(name)
+            .ok_or_else(|| EditError::UnknownFeature(name.to_owned()))?;
+        let (old_group, _) = path.pop()
+            .ok_or(EditError::RootFeature)?;
+        let feature = self.find_feature(name)
+            .ok_or_else(|| EditError::UnknownFeature(name.to_owned()))?;
+        if feature.find(new_parent).is_some() {
+            return Err(EditError::Cycle(name.to_owned()));
+        }
+
+        self.check_group(new_parent, group)?;
+
+        // Empty groups are removed after the feature is added,
+        // so the index of the new group doesn't change when moving within the same parent.
+        let feature = self.detach_feature(name, false)?;
+        self.edit_feature(new_parent, |parent| parent.groups[group].push(feature))?;
+        self.root_feature.edit_at(&path, |old_parent| {
+            if old_parent.groups[old_group].features.is_empty() {
+                old_parent.groups.remove(old_group);
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Changes the cardinality of a group of the parent feature.
+    pub fn set_group_cardinality(&mut self, parent: &str, group: usize, min: usize, max: usize) -> Result<()> {
+        self.check_group(parent, group)?;
+        let n = self.find_feature(parent)
+            .map(|f| f.groups[group].features.len())
+            .unwrap_or_default();
+        if min > max || max > n {
+            return Err(EditError::InvalidCardinality(min, max, n));
+        }
+
+        self.edit_feature(parent, |parent| {
+            let group = &mut parent.groups[group];
+            group.min = min;
+            group.max = max;
+            group.update_config_count();
+        })
+    }
+
+    /// Sets an attribute of the feature, replacing any previous value of the attribute.
+    /// Reals must be finite, since neither UVL nor JSON can represent NaN and infinity.
+    pub fn set_attribute(&mut self, feature: &str, key: impl Into<String>, value: impl Into<Attribute>) -> Result<()> {
+        let (key, value) = (key.into(), value.into());
+        if let Attribute::Real(real) = value && !real.is_finite() {
+            return Err(EditError::NonFiniteAttribute(key));
+        }
+        self.edit_feature(feature, |feature| {
+            feature.attributes.insert(key, value);
+        })
+    }
+
+    /// Adds a cross-tree constraint. Every feature in the constraint must exist in the model.
+    pub fn add_constraint(&mut self, constraint: CrossTreeConstraint) -> Result<()> {
+        if let Some(unknown) = constraint.features().into_iter().find(|&f| self.find_feature(f).is_none()) {
+            return Err(EditError::UnknownFeature(unknown.to_owned()));
+        }
+
+        self.cross_tree_constraints.push(constraint);
+        Ok(())
+    }
+
+    /// Removes the cross-tree constraint at the given index.
+    pub fn remove_constraint(&mut self, index: usize) -> Result<CrossTreeConstraint> {
+        if index >= self.cross_tree_constraints.len() {
+            return Err(EditError::UnknownConstraint(index));
+        }
+
+        Ok(self.cross_tree_constraints.remove(index))
+    }
+
+    fn is_referenced(&self, name: &str) -> bool {
+        self.cross_tree_constraints.iter()
+            .any(|c| c.features().contains(&name))
+    }
+
+    fn check_group(&self, parent: &str, group: usize) -> Result<()> {
+        let feature = self.find_feature(parent)
+            .ok_or_else(|| EditError::UnknownFeature(parent.to_owned()))?;
+        if group >= feature.groups.len() {
+            return Err(EditError::UnknownGroup(parent.to_owned(), group));
+        }
+
+        Ok(())
+    }
+
+    /// Removes a feature from the tree without checking cross-tree constraints.
+    fn detach_feature(&mut self, name: &str, remove_empty_group: bool) -> Result<Feature> {
+        let mut path = self.root_feature.path_to(name)
+            .ok_or_else(|| EditError::UnknownFeature(name.to_owned()))?;
+        let (group, index) = path.pop()
+            .ok_or(EditError::RootFeature)?;
+
+        let feature = self.root_feature.edit_at(&path, |parent| {
+            let feature = parent.groups[group].remove(index);
+            if remove_empty_group && parent.groups[group].features.is_empty() {
+                parent.groups.remove(group);
+            }
+            feature
+        });
+
+        Ok(feature)
+    }
+
+    fn edit_feature<T>(&mut self, name: &str, edit: impl FnOnce(&mut Feature) -> T) -> Result<T> {
+        let path = self.root_feature.path_to(name)
+            .ok_or_else(|| EditError::UnknownFeature(name.to_owned()))?;
+        Ok(self.root_feature.edit_at(&path, edit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{feature::Feature, uvl};
+
+    use super::EditError;
+
+    #[test]
+    fn the_root_feature_can_not_be_moved() {
+        let mut feature_model = uvl::parse("features\n\t\"r\"\n\t\toptional\n\t\t\t\"a\"\n").unwrap();
+
+        assert!(matches!(feature_model.move_feature("r", "a", 0), Err(EditError::RootFeature)));
+        assert!(matches!(feature_model.move_feature("r", "r", 0), Err(EditError::RootFeature)));
+        assert!(matches!(feature_model.move_feature("unknown", "r", 0), Err(EditError::UnknownFeature(_))));
+    }
+
+    #[test]
+    fn adding_features_keeps_the_kind_of_groups() {
+        let mut feature_model = uvl::parse(r#"features
+	"r"
+		alternative
+			"a"
+		mandatory
+			"b"
+			"c"
+		optional
+			"d"
+		or
+			"e"
+			"f"
+"#).unwrap();
+
+        for (group, name) in ["g", "h", "i", "j"].into_iter().enumerate() {
+            feature_model.add_feature("r", group, Feature::new_leaf(name.to_owned(), false)).unwrap();
+        }
+
+        let cardinalities = feature_model.root_feature.groups().iter()
+            .map(|group| (group.min(), group.max()))
+            .collect::<Vec<_>>();
+        assert_eq!(cardinalities, [(1, 1), (3, 3), (0, 2), (1, 3)]);
+    }
+}
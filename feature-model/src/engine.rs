@@ -0,0 +1,442 @@
+use std::collections::HashMap;
+
+use num_bigint::BigUint;
+
+use crate::{FeatureModel, bdd::{Bdd, NodeId}, cross_tree_constraint::CrossTreeConstraint, feature::Feature, group::{Group, GroupKind}};
+
+/// Answers analysis questions about a [FeatureModel] by compiling it into a [Bdd].
+///
+/// Every feature in the model becomes a variable, ordered by a pre-order traversal of the tree,
+/// which keeps related features close to each other.
+/// Features which are only mentioned in cross-tree constraints get a variable after the features of the tree.
+pub struct Engine {
+    pub(crate) bdd: Bdd,
+    pub(crate) root: NodeId,
+    features: Vec<String>,
+    pub(crate) variables: HashMap<String, u32>,
+}
+
+impl Engine {
+    /// Compiles the feature model, including its cross-tree constraints.
+    pub fn new(feature_model: &FeatureModel) -> Engine {
+        let mut engine = Engine {
+            bdd: Bdd::new(),
+            root: NodeId::TRUE,
+            features: vec![],
+            variables: HashMap::new(),
+        };
+
+        engine.root = engine.compile(feature_model);
+        engine
+    }
+
+    /// Compiles another feature model into the same [Bdd], and returns its function.
+    ///
+    /// Features which already have a variable share it,
+    /// so the functions of different models can be compared directly.
+    pub(crate) fn compile(&mut self, feature_model: &FeatureModel) -> NodeId {
+        let mut root = self.compile_tree(feature_model);
+
+        for constraint in &feature_model.cross_tree_constraints {
+            let constraint = self.compile_constraint(constraint);
+            root = self.bdd.and(root, constraint);
+        }
+
+        root
+    }
+
+    /// Compiles the tree of a feature model into the same [Bdd], without its cross-tree constraints.
+    pub(crate) fn compile_tree(&mut self, feature_model: &FeatureModel) -> NodeId {
+        self.add_variables(&feature_model.root_feature);
+        let root_variable = self.variables[&feature_model.root_feature.name];
+        let root_selected = self.bdd.variable(root_variable);
+        let tree = self.compile_feature(&feature_model.root_feature);
+        self.bdd.and(root_selected, tree)
+    }
+
+    /// The names of all features in variable order.
+    pub fn features(&self) -> &[String] {
+        &self.features
+    }
+
+    /// Determines if the model has at least one valid configuration.
+    pub fn is_satisfiable(&self) -> bool {
+        self.root != NodeId::FALSE
+    }
+
+    /// The exact number of valid configurations, taking cross-tree constraints into account.
+    pub fn configurations_number(&self) -> BigUint {
+        self.bdd.count(self.root)
+    }
+
+    /// Determines if a partial configuration can be extended into a valid configuration.
+    ///
+    /// Features of the model which aren't part of the configuration can take any value.
+    /// A configuration which enables a feature that doesn't exist in the model is never satisfiable.
+    pub fn satisfiable_configuration<'a>(&mut self, configuration: impl IntoIterator<Item = (&'a str, bool)>) -> bool {
+        let mut node = self.root;
+        for (feature, enabled) in configuration {
+            match self.variables.get(feature) {
+                Some(&variable) => node = self.bdd.restrict(node, variable, enabled),
+                None if enabled => return false,
+                None => continue,
+            }
+        }
+
+        node != NodeId::FALSE
+    }
+
+    /// Features which are selected in every valid configuration.
+    pub fn core_features(&mut self) -> Vec<&str> {
+        self.features_where(|bdd, root, variable| bdd.restrict(root, variable, false) == NodeId::FALSE)
+    }
+
+    /// Features which can't be selected in any valid configuration.
+    pub fn dead_features(&mut self) -> Vec<&str> {
+        self.features_where(|bdd, root, variable| bdd.restrict(root, variable, true) == NodeId::FALSE)
+    }
+
+    /// Features which are selected in every valid configuration which selects their parent,
+    /// even though they aren't mandatory, where the parent can be selected.
+    ///
+    /// The feature model must be the one the engine was compiled from.
+    pub fn false_optional_features(&mut self, feature_model: &FeatureModel) -> Vec<&str> {
+        let mut variables = vec![];
+        for parent in feature_model.features() {
+            let optional_children = parent.groups.iter()
+                .filter(|g| g.kind() != GroupKind::Mandatory)
+                .flat_map(|g| g.features.iter())
+                .collect::<Vec<_>>();
+            if optional_children.is_empty() {
+                continue;
+            }
+
+            let parent_selected = self.bdd.restrict(self.root, self.variables[&parent.name], true);
+            if parent_selected == NodeId::FALSE {
+                continue;
+            }
+
+            let literals = self.bdd.satisfiable_literals(parent_selected);
+            variables.extend(optional_children.into_iter()
+                .map(|child| self.variables[&child.name])
+                .filter(|&variable| !literals[variable as usize][0]));
+        }
+
+        variables.into_iter()
+            .map(|variable| self.features[variable as usize].as_str())
+            .collect()
+    }
+
+    /// Partitions the features into atomic sets,
+    /// where the features of a set are either all selected or all deselected in every valid configuration.
+    ///
+    /// The core features form a set together, and so do the dead features.
+    /// Sets are ordered by their first feature, and features inside a set are in variable order.
+    pub fn atomic_sets(&mut self) -> Vec<Vec<&str>> {
+        let mut sets: Vec<Vec<u32>> = vec![];
+        let mut assigned = vec![false; self.features.len()];
+
+        for variable in 0..self.features.len() as u32 {
+            if assigned[variable as usize] {
+                continue;
+            }
+
+            // Another feature is equivalent if selecting this feature selects it, and deselecting this feature deselects it.
+            let selected = self.bdd.restrict(self.root, variable, true);
+            let deselected = self.bdd.restrict(self.root, variable, false);
+            let when_selected = self.bdd.satisfiable_literals(selected);
+            let when_deselected = self.bdd.satisfiable_literals(deselected);
+
+            let set = (variable..self.features.len() as u32)
+                .filter(|&other| !assigned[other as usize])
+                .filter(|&other| other == variable || (!when_selected[other as usize][0] && !when_deselected[other as usize][1]))
+                .collect::<Vec<_>>();
+            for &other in &set {
+                assigned[other as usize] = true;
+            }
+            sets.push(set);
+        }
+
+        sets.into_iter()
+            .map(|set| set.into_iter().map(|variable| self.features[variable as usize].as_str()).collect())
+            .collect()
+    }
+
+    fn features_where(&mut self, mut predicate: impl FnMut(&mut Bdd, NodeId, u32) -> bool) -> Vec<&str> {
+        let variables = (0..self.features.len() as u32)
+            .filter(|&variable| predicate(&mut self.bdd, self.root, variable))
+            .collect::<Vec<_>>();
+
+        variables.into_iter()
+            .map(|variable| self.features[variable as usize].as_str())
+            .collect()
+    }
+
+    /// Assigns a variable to every feature in the tree in pre-order.
+    fn add_variables(&mut self, feature: &Feature) {
+        self.variable_for(&feature.name);
+        for group in &feature.groups {
+            for child in &group.features {
+                self.add_variables(child);
+            }
+        }
+    }
+
+    fn variable_for(&mut self, name: &str) -> u32 {
+        if let Some(&variable) = self.variables.get(name) {
+            return variable;
+        }
+
+        let variable = self.bdd.new_variable();
+        self.features.push(name.to_owned());
+        self.variables.insert(name.to_owned(), variable);
+        variable
+    }
+
+    /// Compiles the tree relations below a feature.
+    ///
+    /// Every child implies its parent,
+    /// and a selected parent selects a number of children within the cardinality of each group.
+    fn compile_feature(&mut self, feature: &Feature) -> NodeId {
+        let parent = self.bdd.variable(self.variables[&feature.name]);
+        let mut result = NodeId::TRUE;
+
+        for group in feature.groups.iter().rev() {
+            for child in group.features.iter().rev() {
+                let child_tree = self.compile_feature(child);
+                let child_variable = self.bdd.variable(self.variables[&child.name]);
+                let child_implies_parent = self.bdd.implies(child_variable, parent);
+                let child_relations = self.bdd.and(child_tree, child_implies_parent);
+                result = self.bdd.and(result, child_relations);
+            }
+
+            let cardinality = self.compile_cardinality(group);
+            let parent_implies_cardinality = self.bdd.implies(parent, cardinality);
+            result = self.bdd.and(result, parent_implies_cardinality);
+        }
+
+        result
+    }
+
+    /// Compiles the function which is true when the number of selected features
+    /// in the group is within the group's cardinality.
+    pub(crate) fn compile_cardinality(&mut self, group: &Group) -> NodeId {
+        let mut variables = group.features.iter()
+            .map(|f| self.variables[&f.name])
+            .collect::<Vec<_>>();
+        variables.sort();
+        variables.dedup();
+
+        // layer[k] is the function over the remaining variables, given that k features have been selected so far.
+        let n = variables.len();
+        let mut layer = (0..=n)
+            .map(|k| if group.min <= k && k <= group.max { NodeId::TRUE } else { NodeId::FALSE })
+            .collect::<Vec<_>>();
+
+        for (i, &variable) in variables.iter().enumerate().rev() {
+            layer = (0..=i)
+                .map(|k| self.bdd.make(variable, layer[k], layer[k + 1]))
+                .collect();
+        }
+
+        layer[0]
+    }
+
+    pub(crate) fn compile_constraint(&mut self, constraint: &CrossTreeConstraint) -> NodeId {
+        match constraint {
+            CrossTreeConstraint::Feature(name) => {
+                let variable = self.variable_for(name);
+                self.bdd.variable(variable)
+            }
+            CrossTreeConstraint::And(operands) => operands.iter().fold(NodeId::TRUE, |result, operand| {
+                let operand = self.compile_constraint(operand);
+                self.bdd.and(result, operand)
+            }),
+            CrossTreeConstraint::Or(operands) => operands.iter().fold(NodeId::FALSE, |result, operand| {
+                let operand = self.compile_constraint(operand);
+                self.bdd.or(result, operand)
+            }),
+            CrossTreeConstraint::Implies(a, b) => {
+                let a = self.compile_constraint(a);
+                let b = self.compile_constraint(b);
+                self.bdd.implies(a, b)
+            }
+            CrossTreeConstraint::Equiv(a, b) => {
+                let a = self.compile_constraint(a);
+                let b = self.compile_constraint(b);
+                self.bdd.equivalent(a, b)
+            }
+            CrossTreeConstraint::Not(a) => {
+                let a = self.compile_constraint(a);
+                self.bdd.not(a)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use num_bigint::BigUint;
+
+    use crate::{FeatureModel, feature::Feature, uvl};
+
+    use super::Engine;
+
+    type Configuration = HashSet<String>;
+    type Constraints = fn(&Configuration) -> bool;
+
+    /// Models with every kind of group, a cardinality group and a feature which only appears in a constraint,
+    /// together with their cross-tree constraints as a predicate for the brute force enumeration.
+    fn models() -> Vec<(FeatureModel, Constraints)> {
+        let groups = r#"features
+    "r"
+        mandatory
+            "m"
+        optional
+            "o"
+                alternative
+                    "x"
+                    "y"
+                    "z"
+        [2..3]
+            "a"
+            "b"
+            "c"
+            "d"
+        or
+            "p"
+            "q"
+constraints
+    "x" => "a" & !"b"
+    "p" | "extra"
+"#;
+        let anomalies = r#"features
+    "r"
+        optional
+            "a"
+            "b"
+                optional
+                    "c"
+            "d"
+        mandatory
+            "e"
+constraints
+    "e" => "a"
+    "a" => "b"
+    "b" => "c"
+    !"d"
+"#;
+        let void = r#"features
+    "r"
+        optional
+            "a"
+constraints
+    !"r"
+"#;
+
+        vec![
+            (uvl::parse(groups).unwrap(), |c| (!c.contains("x") || (c.contains("a") && !c.contains("b"))) && (c.contains("p") || c.contains("extra"))),
+            (uvl::parse(anomalies).unwrap(), |c| (!c.contains("e") || c.contains("a")) && (!c.contains("a") || c.contains("b")) && (!c.contains("b") || c.contains("c")) && !c.contains("d")),
+            (uvl::parse(void).unwrap(), |c| !c.contains("r")),
+        ]
+    }
+
+    fn tree_holds(feature: &Feature, configuration: &Configuration) -> bool {
+        let parent = configuration.contains(&feature.name);
+        feature.groups.iter().all(|group| {
+            let selected = group.features.iter().filter(|f| configuration.contains(&f.name)).count();
+            (selected == 0 || parent)
+                && (!parent || (group.min..=group.max).contains(&selected))
+                && group.features.iter().all(|f| tree_holds(f, configuration))
+        })
+    }
+
+    /// Every valid configuration, found by enumerating all assignments of the features of the engine.
+    fn valid_configurations(engine: &Engine, feature_model: &FeatureModel, constraints: Constraints) -> Vec<Configuration> {
+        let features = engine.features();
+        (0..1_u32 << features.len())
+            .map(|bits| features.iter()
+                .enumerate()
+                .filter(|(i, _)| bits >> i & 1 == 1)
+                .map(|(_, f)| f.clone())
+                .collect::<Configuration>())
+            .filter(|c| c.contains(&feature_model.root_feature.name) && tree_holds(&feature_model.root_feature, c) && constraints(c))
+            .collect()
+    }
+
+    #[test]
+    fn counts_match_brute_force() {
+        for (feature_model, constraints) in models() {
+            let engine = Engine::new(&feature_model);
+            let valid = valid_configurations(&engine, &feature_model, constraints);
+            assert_eq!(engine.configurations_number(), BigUint::from(valid.len()));
+            assert_eq!(engine.is_satisfiable(), !valid.is_empty());
+        }
+    }
+
+    #[test]
+    fn partial_configurations_match_brute_force() {
+        for (feature_model, constraints) in models() {
+            let mut engine = Engine::new(&feature_model);
+            let valid = valid_configurations(&engine, &feature_model, constraints);
+            let features = engine.features().to_vec();
+
+            for a in &features {
+                for b in features.iter().filter(|b| *b != a) {
+                    for (a_selected, b_selected) in [(false, false), (false, true), (true, false), (true, true)] {
+                        let expected = valid.iter().any(|c| c.contains(a) == a_selected && c.contains(b) == b_selected);
+                        assert_eq!(engine.satisfiable_configuration([(a.as_str(), a_selected), (b.as_str(), b_selected)]), expected);
+                    }
+                }
+            }
+
+            assert!(!engine.satisfiable_configuration([("unknown", true)]));
+            assert_eq!(engine.satisfiable_configuration([("unknown", false)]), !valid.is_empty());
+        }
+    }
+
+    #[test]
+    fn feature_properties_match_brute_force() {
+        for (feature_model, constraints) in models() {
+            let mut engine = Engine::new(&feature_model);
+            let valid = valid_configurations(&engine, &feature_model, constraints);
+            let features = engine.features().to_vec();
+            let owned = |features: Vec<&str>| features.into_iter().map(str::to_owned).collect::<Vec<_>>();
+
+            let core = features.iter().filter(|f| valid.iter().all(|c| c.contains(*f))).cloned().collect::<Vec<_>>();
+            let dead = features.iter().filter(|f| valid.iter().all(|c| !c.contains(*f))).cloned().collect::<Vec<_>>();
+            assert_eq!(owned(engine.core_features()), core);
+            assert_eq!(owned(engine.dead_features()), dead);
+
+            let mut false_optional = vec![];
+            for parent in feature_model.features() {
+                let with_parent = valid.iter().filter(|c| c.contains(&parent.name)).collect::<Vec<_>>();
+                let optional_children = parent.groups.iter()
+                    .filter(|g| !g.is_mandatory())
+                    .flat_map(|g| g.features.iter());
+                for child in optional_children {
+                    if !with_parent.is_empty() && with_parent.iter().all(|c| c.contains(&child.name)) {
+                        false_optional.push(child.name.clone());
+                    }
+                }
+            }
+            let mut found = owned(engine.false_optional_features(&feature_model));
+            false_optional.sort();
+            found.sort();
+            assert_eq!(found, false_optional);
+
+            // Two features are in the same atomic set if every valid configuration selects both or neither.
+            let mut atomic_sets: Vec<Vec<String>> = vec![];
+            for feature in &features {
+                match atomic_sets.iter_mut().find(|set| valid.iter().all(|c| c.contains(&set[0]) == c.contains(feature))) {
+                    Some(set) => set.push(feature.clone()),
+                    None => atomic_sets.push(vec![feature.clone()]),
+                }
+            }
+            let found = engine.atomic_sets().into_iter().map(owned).collect::<Vec<_>>();
+            assert_eq!(found, atomic_sets);
+        }
+    }
+}
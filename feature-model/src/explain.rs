@@ -0,0 +1,134 @@
+use std::fmt::Display;
+
+use crate::{FeatureModel, bdd::{Bdd, NodeId}, cross_tree_constraint::CrossTreeConstraint, engine::Engine, group::GroupKind};
+
+/// A single relation of a feature model, which can reject a configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Relation {
+    /// The root feature is selected in every configuration.
+    Root(String),
+    /// A selected child selects its parent.
+    Parent {
+        child: String,
+        parent: String,
+    },
+    /// A selected parent selects a number of features of the group within its cardinality.
+    Group {
+        parent: String,
+        kind: GroupKind,
+        features: Vec<String>,
+    },
+    Constraint(CrossTreeConstraint),
+    /// The configuration selects a feature which doesn't exist in the model.
+    UnknownFeature(String),
+}
+
+/// Explains why configurations are rejected by a feature model.
+///
+/// Every relation of the model is compiled into its own function of a shared [crate::bdd::Bdd],
+/// so the relations can be checked against a configuration independently of each other.
+pub struct Explainer {
+    engine: Engine,
+    relations: Vec<(Relation, NodeId)>,
+}
+
+impl Explainer {
+    pub fn new(feature_model: &FeatureModel) -> Explainer {
+        let mut engine = Engine::new(feature_model);
+        let mut relations = vec![];
+
+        let root = &feature_model.root_feature.name;
+        let root_selected = engine.bdd.variable(engine.variables[root]);
+        relations.push((Relation::Root(root.clone()), root_selected));
+
+        for parent in feature_model.features() {
+            let parent_variable = engine.bdd.variable(engine.variables[&parent.name]);
+            for group in &parent.groups {
+                for child in &group.features {
+                    let child_variable = engine.bdd.variable(engine.variables[&child.name]);
+                    let child_implies_parent = engine.bdd.implies(child_variable, parent_variable);
+                    relations.push((Relation::Parent { child: child.name.clone(), parent: parent.name.clone() }, child_implies_parent));
+                }
+
+                let cardinality = engine.compile_cardinality(group);
+                let parent_implies_cardinality = engine.bdd.implies(parent_variable, cardinality);
+                let features = group.features.iter().map(|f| f.name.clone()).collect();
+                relations.push((Relation::Group { parent: parent.name.clone(), kind: group.kind(), features }, parent_implies_cardinality));
+            }
+        }
+
+        for constraint in &feature_model.cross_tree_constraints {
+            let node = engine.compile_constraint(constraint);
+            relations.push((Relation::Constraint(constraint.clone()), node));
+        }
+
+        Explainer { engine, relations }
+    }
+
+    /// Finds a minimal set of relations which reject the configuration,
+    /// or None if the configuration is accepted.
+    ///
+    /// Features of the model which aren't part of the configuration can take any value,
+    /// like in [Engine::satisfiable_configuration].
+    /// The relations are minimal in the sense that the configuration is accepted
+    /// when any one of them is removed from the model, but there can be other minimal sets.
+    pub fn explain<'a>(&mut self, configuration: impl IntoIterator<Item = (&'a str, bool)>) -> Option<Vec<Relation>> {
+        let mut assignment = vec![];
+        for (feature, enabled) in configuration {
+            match self.engine.variables.get(feature) {
+                Some(&variable) => assignment.push((variable, enabled)),
+                None if enabled => return Some(vec![Relation::UnknownFeature(feature.to_owned())]),
+                None => continue,
+            }
+        }
+
+        let bdd = &mut self.engine.bdd;
+        let restricted = self.relations.iter()
+            .enumerate()
+            .map(|(i, &(_, node))| (i, assignment.iter().fold(node, |node, &(variable, value)| bdd.restrict(node, variable, value))))
+            .filter(|&(_, node)| node != NodeId::TRUE)
+            .collect::<Vec<_>>();
+
+        if let Some(&(i, _)) = restricted.iter().find(|&&(_, node)| node == NodeId::FALSE) {
+            return Some(vec![self.relations[i].0.clone()]);
+        }
+
+        let conjunction = |bdd: &mut Bdd, relations: &[(usize, NodeId)]| relations.iter()
+            .fold(NodeId::TRUE, |result, &(_, node)| bdd.and(result, node));
+
+        if conjunction(bdd, &restricted) != NodeId::FALSE {
+            return None;
+        }
+
+        // Removes every relation which isn't needed to reject the configuration.
+        let mut core = restricted;
+        let mut i = 0;
+        while i < core.len() {
+            let others = [&core[..i], &core[i + 1..]].concat();
+            if conjunction(bdd, &others) == NodeId::FALSE {
+                core = others;
+            } else {
+                i += 1;
+            }
+        }
+
+        Some(core.into_iter()
+            .map(|(i, _)| self.relations[i].0.clone())
+            .collect())
+    }
+}
+
+impl Display for Relation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Relation::Root(root) => write!(f, "\"{root}\" is the root feature"),
+            Relation::Parent { child, parent } => write!(f, "\"{child}\" requires its parent \"{parent}\""),
+            Relation::Group { parent, kind, features } => {
+                let features = features.iter().map(|f| format!("\"{f}\"")).collect::<Vec<_>>().join(", ");
+                write!(f, "\"{parent}\" has the {kind} group {features}")
+            }
+            Relation::Constraint(constraint) => write!(f, "constraint {constraint}"),
+            Relation::UnknownFeature(feature) => write!(f, "\"{feature}\" doesn't exist in the feature model"),
+        }
+    }
+}
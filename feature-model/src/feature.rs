@@ -1,31 +1,144 @@
-use crate::group::Group;
+use std::collections::BTreeMap;
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::{attribute::Attribute, count, group::Group};
 
 /// Represents a feature inside a feature model.
 /// Stores a name and a collection of groups.
-/// A feature can be marked as abstract.
+/// A feature can be marked as abstract, and can carry typed attributes.
+/// 
+/// The number of configurations of the feature's subtree is cached in config_count,
+/// which ignores cross-tree constraints.
+/// To keep the cache consistent, the groups can only be changed through the editing API of [crate::FeatureModel],
+/// which also checks the attributes.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "FeatureData")]
 pub struct Feature {
     pub name: String,
-    pub groups: Vec<Group>,
+    #[serde(rename = "abstract", skip_serializing_if = "std::ops::Not::not")]
     pub is_abstract: bool,
-    pub config_count: f64,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) attributes: BTreeMap<String, Attribute>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) groups: Vec<Group>,
+    #[serde(skip)]
+    pub(crate) config_count: BigUint,
+}
+
+/// The serialized form of a [Feature], whose number of configurations is computed when it is deserialized.
+#[derive(Deserialize)]
+struct FeatureData {
+    name: String,
+    #[serde(rename = "abstract", default)]
+    is_abstract: bool,
+    #[serde(default)]
+    attributes: BTreeMap<String, Attribute>,
+    #[serde(default)]
+    groups: Vec<Group>,
+}
+
+impl From<FeatureData> for Feature {
+    fn from(data: FeatureData) -> Self {
+        let mut feature = Feature::new(data.name, data.groups, data.is_abstract);
+        feature.attributes = data.attributes;
+        feature
+    }
 }
 
 impl Feature {
     pub fn new(name: String, groups: Vec<Group>, is_abstract: bool) -> Feature {
         let config_count = groups.iter()
-            .map(|g| g.config_count)
+            .map(|g| &g.config_count)
             .product();
 
-        Feature { name, groups, is_abstract, config_count }
+        Feature { name, groups, is_abstract, attributes: BTreeMap::new(), config_count }
     }
 
     pub fn new_leaf(name: String, is_abstract: bool) -> Feature {
         Feature::new(name, vec![], is_abstract)
     }
 
+    pub fn attributes(&self) -> &BTreeMap<String, Attribute> {
+        &self.attributes
+    }
+
+    pub fn groups(&self) -> &[Group] {
+        &self.groups
+    }
+
+    /// The number of configurations of the feature's subtree, ignoring cross-tree constraints.
+    pub fn config_count(&self) -> &BigUint {
+        &self.config_count
+    }
+
+    /// The base 10 logarithm of the number of configurations of the feature's subtree.
+    /// Useful for plotting, where the exact count can be too large for a f64.
+    pub fn log_config_count(&self) -> f64 {
+        count::log10(&self.config_count)
+    }
+
     pub fn count_features(&self) -> usize {
         self.groups.iter()
             .flat_map(|g| g.features.iter())
             .count()
     }
+
+    /// Iterates over the feature and all of its descendants in pre-order.
+    pub fn iter(&self) -> impl Iterator<Item = &Feature> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let feature = stack.pop()?;
+            let children = feature.groups.iter()
+                .flat_map(|g| g.features.iter())
+                .rev();
+            stack.extend(children);
+            Some(feature)
+        })
+    }
+
+    /// Finds the feature with the given name in the subtree.
+    pub fn find(&self, name: &str) -> Option<&Feature> {
+        self.iter().find(|f| f.name == name)
+    }
+
+    /// Finds the path from this feature to the feature with the given name.
+    /// Every step of the path is the index of a group and the index of a feature inside that group.
+    pub(crate) fn path_to(&self, name: &str) -> Option<Vec<(usize, usize)>> {
+        if self.name == name {
+            return Some(vec![]);
+        }
+
+        self.groups.iter()
+            .enumerate()
+            .flat_map(|(i, g)| g.features.iter().enumerate().map(move |(j, f)| (i, j, f)))
+            .find_map(|(i, j, f)| {
+                let mut path = f.path_to(name)?;
+                path.insert(0, (i, j));
+                Some(path)
+            })
+    }
+
+    /// Applies an edit to the feature at the end of the path,
+    /// and updates the cached counts of every feature and group along the path.
+    pub(crate) fn edit_at<T>(&mut self, path: &[(usize, usize)], edit: impl FnOnce(&mut Feature) -> T) -> T {
+        let result = match path.split_first() {
+            None => edit(self),
+            Some((&(i, j), rest)) => {
+                let result = self.groups[i].features[j].edit_at(rest, edit);
+                self.groups[i].update_config_count();
+                result
+            }
+        };
+
+        self.update_config_count();
+        result
+    }
+
+    pub(crate) fn update_config_count(&mut self) {
+        self.config_count = self.groups.iter()
+            .map(|g| &g.config_count)
+            .product();
+    }
 }
@@ -0,0 +1,158 @@
+use std::io::Write;
+
+use crate::{FeatureModel, cross_tree_constraint::CrossTreeConstraint, feature::Feature, group::{Group, GroupKind}, indent::tab, xml::escape};
+
+mod parse;
+
+pub use parse::{parse, ParseError, ParseErrorKind};
+
+/// Error returned when a feature model can't be written as FeatureIDE XML.
+#[derive(Debug, thiserror::Error)]
+pub enum WriteError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("The group [{min}..{max}] with {size} features below '{feature}' can't be represented in FeatureIDE, it needs to be rewritten using abstract features")]
+    UnsupportedGroup {
+        feature: String,
+        min: usize,
+        max: usize,
+        size: usize,
+    },
+    #[error("'{0}' has an or-group or alternative-group together with other groups, which can't be represented in FeatureIDE, every group needs to be moved below its own abstract feature")]
+    MixedGroups(String),
+}
+
+/// The type of a feature in FeatureIDE, which decides the relation to its children.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+    Leaf,
+    And,
+    Or,
+    Alt,
+}
+
+impl NodeKind {
+    fn tag(self) -> &'static str {
+        match self {
+            NodeKind::Leaf => "feature",
+            NodeKind::And => "and",
+            NodeKind::Or => "or",
+            NodeKind::Alt => "alt",
+        }
+    }
+}
+
+/// Writes the feature model in the XML format of FeatureIDE.
+///
+/// FeatureIDE has a single relation between a feature and its children,
+/// so features can have any number of mandatory and optional groups,
+/// or a single or-group or alternative-group.
+/// The model is checked before anything is written.
+pub fn write<W: Write>(writer: &mut W, feature_model: &FeatureModel) -> Result<(), WriteError> {
+    for feature in feature_model.root_feature.iter() {
+        node_kind(feature)?;
+    }
+
+    writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>")?;
+    writeln!(writer, "<featureModel>")?;
+    tab(writer, 1)?;
+    writeln!(writer, "<struct>")?;
+    write_feature(writer, &feature_model.root_feature, true, 2)?;
+    tab(writer, 1)?;
+    writeln!(writer, "</struct>")?;
+
+    if !feature_model.cross_tree_constraints.is_empty() {
+        tab(writer, 1)?;
+        writeln!(writer, "<constraints>")?;
+        for constraint in &feature_model.cross_tree_constraints {
+            tab(writer, 2)?;
+            writeln!(writer, "<rule>")?;
+            write_constraint(writer, constraint, 3)?;
+            tab(writer, 2)?;
+            writeln!(writer, "</rule>")?;
+        }
+        tab(writer, 1)?;
+        writeln!(writer, "</constraints>")?;
+    }
+
+    writeln!(writer, "</featureModel>")?;
+    Ok(())
+}
+
+fn node_kind(feature: &Feature) -> Result<NodeKind, WriteError> {
+    let groups = feature.groups.iter()
+        .filter(|g| !g.features.is_empty())
+        .collect::<Vec<_>>();
+
+    if groups.is_empty() {
+        return Ok(NodeKind::Leaf);
+    }
+
+    let group_kind = |group: &Group| match group.kind() {
+        GroupKind::Mandatory | GroupKind::Optional => Ok(NodeKind::And),
+        GroupKind::Or => Ok(NodeKind::Or),
+        GroupKind::Alternative => Ok(NodeKind::Alt),
+        GroupKind::Cardinality(min, max) => Err(WriteError::UnsupportedGroup { feature: feature.name.clone(), min, max, size: group.features.len() }),
+    };
+
+    let kinds = groups.into_iter()
+        .map(group_kind)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match kinds.as_slice() {
+        [kind] => Ok(*kind),
+        kinds if kinds.iter().all(|&k| k == NodeKind::And) => Ok(NodeKind::And),
+        _ => Err(WriteError::MixedGroups(feature.name.clone())),
+    }
+}
+
+fn write_feature<W: Write>(writer: &mut W, feature: &Feature, is_mandatory: bool, depth: usize) -> Result<(), WriteError> {
+    let kind = node_kind(feature)?;
+
+    tab(writer, depth)?;
+    write!(writer, "<{}", kind.tag())?;
+    if feature.is_abstract {
+        write!(writer, " abstract=\"true\"")?;
+    }
+    if is_mandatory {
+        write!(writer, " mandatory=\"true\"")?;
+    }
+    write!(writer, " name=\"{}\"", escape(&feature.name))?;
+
+    if kind == NodeKind::Leaf {
+        writeln!(writer, "/>")?;
+        return Ok(());
+    }
+
+    writeln!(writer, ">")?;
+    for group in &feature.groups {
+        let is_mandatory = kind == NodeKind::And && group.is_mandatory();
+        for child in &group.features {
+            write_feature(writer, child, is_mandatory, depth + 1)?;
+        }
+    }
+    tab(writer, depth)?;
+    writeln!(writer, "</{}>", kind.tag())?;
+
+    Ok(())
+}
+
+fn write_constraint<W: Write>(writer: &mut W, constraint: &CrossTreeConstraint, depth: usize) -> std::io::Result<()> {
+    tab(writer, depth)?;
+
+    let (tag, operands) = match constraint {
+        CrossTreeConstraint::Feature(name) => return writeln!(writer, "<var>{}</var>", escape(name)),
+        CrossTreeConstraint::And(operands) => ("conj", operands.iter().collect::<Vec<_>>()),
+        CrossTreeConstraint::Or(operands) => ("disj", operands.iter().collect()),
+        CrossTreeConstraint::Implies(a, b) => ("imp", vec![a.as_ref(), b.as_ref()]),
+        CrossTreeConstraint::Equiv(a, b) => ("eq", vec![a.as_ref(), b.as_ref()]),
+        CrossTreeConstraint::Not(a) => ("not", vec![a.as_ref()]),
+    };
+
+    writeln!(writer, "<{tag}>")?;
+    for operand in operands {
+        write_constraint(writer, operand, depth + 1)?;
+    }
+    tab(writer, depth)?;
+    writeln!(writer, "</{tag}>")
+}
@@ -0,0 +1,177 @@
+use roxmltree::{Document, Node};
+
+use crate::{FeatureModel, cross_tree_constraint::{self, CrossTreeConstraint}, feature::Feature, group::Group};
+
+/// Error returned when a FeatureIDE document can't be parsed.
+/// Lines and columns are 1-indexed.
+#[derive(Debug, thiserror::Error)]
+#[error("Error at line {line}, column {column}: {kind}")]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ParseErrorKind {
+    #[error("Invalid XML: {0}")]
+    Xml(roxmltree::Error),
+    #[error("Expected the root element 'featureModel', found '{0}'")]
+    MissingFeatureModel(String),
+    #[error("Expected the element 'struct'")]
+    MissingStruct,
+    #[error("Expected a root feature")]
+    MissingRootFeature,
+    #[error("A feature model can only have one root feature")]
+    MultipleRootFeatures,
+    #[error("Expected the attribute 'name' on '{0}'")]
+    MissingName(String),
+    #[error("Expected a constraint")]
+    ExpectedConstraint,
+    #[error("Unknown constraint element '{0}'")]
+    UnknownConstraint(String),
+    #[error("'{tag}' expects {expected} operands, found {found}")]
+    InvalidOperandCount {
+        tag: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+/// Parses a feature model from the XML format of FeatureIDE.
+///
+/// The structure supports and, or and alt features with the abstract and mandatory attributes.
+/// The children of an and feature are split into a mandatory and an optional group.
+/// Constraints support var, not, conj, disj, imp, eq and atmost1.
+/// Elements without a meaning for the feature model, like descriptions and properties, are ignored.
+pub fn parse(input: &str) -> Result<FeatureModel> {
+    let document = Document::parse(input)
+        .map_err(|e| {
+            let position = e.pos();
+            ParseError { line: position.row as usize, column: position.col as usize, kind: ParseErrorKind::Xml(e) }
+        })?;
+
+    let feature_model = document.root_element();
+    if feature_model.tag_name().name() != "featureModel" {
+        let name = feature_model.tag_name().name().to_owned();
+        return Err(error(feature_model, ParseErrorKind::MissingFeatureModel(name)));
+    }
+
+    let structure = child_elements(feature_model)
+        .find(|n| n.tag_name().name() == "struct")
+        .ok_or_else(|| error(feature_model, ParseErrorKind::MissingStruct))?;
+
+    let mut roots = child_elements(structure).filter(|&n| is_feature(n));
+    let root = roots.next()
+        .ok_or_else(|| error(structure, ParseErrorKind::MissingRootFeature))?;
+    if let Some(other) = roots.next() {
+        return Err(error(other, ParseErrorKind::MultipleRootFeatures));
+    }
+    let root_feature = parse_feature(root)?;
+
+    let cross_tree_constraints = child_elements(feature_model)
+        .filter(|n| n.tag_name().name() == "constraints")
+        .flat_map(child_elements)
+        .filter(|n| n.tag_name().name() == "rule")
+        .map(parse_rule)
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(FeatureModel::new(root_feature, cross_tree_constraints))
+}
+
+fn parse_feature(node: Node) -> Result<Feature> {
+    let name = node.attribute("name")
+        .ok_or_else(|| error(node, ParseErrorKind::MissingName(node.tag_name().name().to_owned())))?
+        .to_owned();
+    let is_abstract = node.attribute("abstract") == Some("true");
+
+    let children = child_elements(node)
+        .filter(|&n| is_feature(n))
+        .map(|n| parse_feature(n).map(|feature| (feature, n.attribute("mandatory") == Some("true"))))
+        .collect::<Result<Vec<_>>>()?;
+
+    let groups = match node.tag_name().name() {
+        _ if children.is_empty() => vec![],
+        "or" => {
+            let n = children.len();
+            vec![Group::new(children.into_iter().map(|(f, _)| f).collect(), 1, n)]
+        }
+        "alt" => vec![Group::new(children.into_iter().map(|(f, _)| f).collect(), 1, 1)],
+        _ => {
+            let (mandatory, optional): (Vec<_>, Vec<_>) = children.into_iter().partition(|(_, m)| *m);
+            let mandatory = mandatory.into_iter().map(|(f, _)| f).collect::<Vec<_>>();
+            let optional = optional.into_iter().map(|(f, _)| f).collect::<Vec<_>>();
+            [Group::mandatory(mandatory), Group::optional(optional)].into_iter()
+                .filter(|g| !g.features.is_empty())
+                .collect()
+        }
+    };
+
+    Ok(Feature::new(name, groups, is_abstract))
+}
+
+fn parse_rule(node: Node) -> Result<CrossTreeConstraint> {
+    let expression = child_elements(node)
+        .find(|n| n.tag_name().name() != "description")
+        .ok_or_else(|| error(node, ParseErrorKind::ExpectedConstraint))?;
+    parse_constraint(expression)
+}
+
+fn parse_constraint(node: Node) -> Result<CrossTreeConstraint> {
+    let tag = node.tag_name().name();
+    if tag == "var" {
+        let name = node.text().unwrap_or_default().trim();
+        return Ok(CrossTreeConstraint::Feature(name.to_owned()));
+    }
+
+    let operands = child_elements(node)
+        .map(parse_constraint)
+        .collect::<Result<Vec<_>>>()?;
+    let operand_count = |expected: usize| if operands.len() == expected {
+        Ok(())
+    } else {
+        Err(error(node, ParseErrorKind::InvalidOperandCount { tag: tag.to_owned(), expected, found: operands.len() }))
+    };
+
+    match tag {
+        "conj" => Ok(CrossTreeConstraint::And(operands)),
+        "disj" => Ok(CrossTreeConstraint::Or(operands)),
+        "not" => {
+            operand_count(1)?;
+            let [a] = <[_; 1]>::try_from(operands).expect("Operand count is checked");
+            Ok(cross_tree_constraint::not(a))
+        }
+        "imp" => {
+            operand_count(2)?;
+            let [a, b] = <[_; 2]>::try_from(operands).expect("Operand count is checked");
+            Ok(cross_tree_constraint::implies(a, b))
+        }
+        "eq" => {
+            operand_count(2)?;
+            let [a, b] = <[_; 2]>::try_from(operands).expect("Operand count is checked");
+            Ok(cross_tree_constraint::equivalent(a, b))
+        }
+        "atmost1" => {
+            let pairs = operands.iter()
+                .enumerate()
+                .flat_map(|(i, a)| operands[i + 1..].iter().map(move |b| cross_tree_constraint::exclusive(a.clone(), b.clone())));
+            Ok(cross_tree_constraint::conjunction(pairs))
+        }
+        _ => Err(error(node, ParseErrorKind::UnknownConstraint(tag.to_owned()))),
+    }
+}
+
+fn is_feature(node: Node) -> bool {
+    matches!(node.tag_name().name(), "feature" | "and" | "or" | "alt")
+}
+
+fn child_elements<'a, 'input>(node: Node<'a, 'input>) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(|n| n.is_element())
+}
+
+fn error(node: Node, kind: ParseErrorKind) -> ParseError {
+    let position = node.document().text_pos_at(node.range().start);
+    ParseError { line: position.row as usize, column: position.col as usize, kind }
+}
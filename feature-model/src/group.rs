@@ -1,35 +1,72 @@
-use crate::feature::Feature;
+use std::fmt::Display;
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::{count, feature::Feature};
 
 /// Represents a group inside a feature model.
 /// A group stores a collection of features,
 /// and a minimum and maximum cardinality.
+/// 
+/// Like [Feature], the number of configurations is cached,
+/// so the group can only be changed through the editing API of [crate::FeatureModel].
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "GroupData")]
 pub struct Group {
-    pub features: Vec<Feature>,
-    pub min: usize,
-    pub max: usize,
-    pub config_count: f64,
+    pub(crate) min: usize,
+    pub(crate) max: usize,
+    pub(crate) features: Vec<Feature>,
+    #[serde(skip)]
+    pub(crate) config_count: BigUint,
 }
 
-impl Group {
-    pub fn new(features: Vec<Feature>, min: usize, max: usize) -> Group {
-        let n = features.len();
-        let mut dp = vec![0.0; n + 1];
-        dp[0] = 1.0;
+/// The serialized form of a [Group], whose cardinality is checked and whose number of configurations is computed when it is deserialized.
+#[derive(Deserialize)]
+struct GroupData {
+    min: usize,
+    max: usize,
+    features: Vec<Feature>,
+}
 
-        #[allow(clippy::needless_range_loop)]
-        for i in 0..n {
-            for k in (1..=n).rev() {
-                dp[k] += dp[k - 1] * features[i].config_count;
-            }
+impl TryFrom<GroupData> for Group {
+    type Error = String;
+
+    fn try_from(data: GroupData) -> Result<Self, Self::Error> {
+        let n = data.features.len();
+        if data.min > data.max || data.max > n {
+            return Err(format!("Invalid cardinality [{}..{}] for a group with {n} features", data.min, data.max));
         }
 
-        let config_count = (min..=max)
-            .map(|k| dp[k])
-            .sum();
+        Ok(Group::new(data.features, data.min, data.max))
+    }
+}
+
+impl Group {
+    pub fn new(features: Vec<Feature>, min: usize, max: usize) -> Group {
+        let weights = features.iter().map(|f| &f.config_count);
+        let config_count = count::cardinality_count(weights, min, max);
 
         Group { features, min, max, config_count }
     }
 
+    pub fn features(&self) -> &[Feature] {
+        &self.features
+    }
+
+    pub fn min(&self) -> usize {
+        self.min
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// The number of configurations of the group, ignoring cross-tree constraints.
+    pub fn config_count(&self) -> &BigUint {
+        &self.config_count
+    }
+
     /// Creates a new mandatory group.
     pub fn mandatory(features: Vec<Feature>) -> Group {
         let n = features.len();
@@ -42,6 +79,18 @@ impl Group {
         Group::new(features, 0, n)
     }
 
+    /// The kind of the group, decided by its cardinality.
+    pub fn kind(&self) -> GroupKind {
+        let n = self.features.len();
+        match (self.min, self.max) {
+            (a, b) if a == b && b == n => GroupKind::Mandatory,
+            (0, b) if b == n => GroupKind::Optional,
+            (1, b) if b == n => GroupKind::Or,
+            (1, 1) => GroupKind::Alternative,
+            (a, b) => GroupKind::Cardinality(a, b),
+        }
+    }
+
     /// Determines if the group is mandatory.
     pub fn is_mandatory(&self) -> bool {
         let n = self.features.len();
@@ -52,4 +101,67 @@ impl Group {
     pub fn is_optional(&self) -> bool {
         self.min == 0 && self.max == self.features.len()
     }
-}
\ No newline at end of file
+
+    /// Adds a feature to the group.
+    /// 
+    /// A cardinality bound which is equal to the number of features is increased along with it,
+    /// so mandatory, optional and or groups keep their kind.
+    /// An empty group becomes optional.
+    /// A group with one feature and the cardinality [1..1] may as well be an alternative,
+    /// so it keeps its bounds instead of making the new feature mandatory.
+    pub(crate) fn push(&mut self, feature: Feature) {
+        let n = self.features.len();
+        if !(n == 1 && self.min == 1 && self.max == 1) {
+            if self.min == n && n > 0 {
+                self.min += 1;
+            }
+            if self.max == n {
+                self.max += 1;
+            }
+        }
+        self.features.push(feature);
+        self.update_config_count();
+    }
+
+    /// Removes a feature from the group.
+    /// 
+    /// Cardinality bounds are decreased when they would exceed the number of features,
+    /// so mandatory, optional and or groups keep their kind.
+    pub(crate) fn remove(&mut self, index: usize) -> Feature {
+        let feature = self.features.remove(index);
+        let n = self.features.len();
+        self.max = self.max.min(n);
+        self.min = self.min.min(n);
+        self.update_config_count();
+        feature
+    }
+
+    pub(crate) fn update_config_count(&mut self) {
+        let weights = self.features.iter().map(|f| &f.config_count);
+        self.config_count = count::cardinality_count(weights, self.min, self.max);
+    }
+}
+
+/// The kinds of groups in a feature diagram.
+/// Groups which don't match any of the named kinds are described by their cardinality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupKind {
+    Mandatory,
+    Optional,
+    Or,
+    Alternative,
+    Cardinality(usize, usize),
+}
+
+/// Writes the kind with the keywords of UVL.
+impl Display for GroupKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GroupKind::Mandatory => write!(f, "mandatory"),
+            GroupKind::Optional => write!(f, "optional"),
+            GroupKind::Or => write!(f, "or"),
+            GroupKind::Alternative => write!(f, "alternative"),
+            GroupKind::Cardinality(min, max) => write!(f, "[{min}..{max}]"),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{FeatureModel, cross_tree_constraint::CrossTreeConstraint, feature::Feature};
+
+/// The version of the JSON schema, which is increased whenever the schema changes incompatibly.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Writes the feature model as pretty-printed JSON.
+///
+/// The document is an object with the schema_version, the root feature and the constraints.
+/// A feature has a name, and optionally abstract, attributes and groups,
+/// a group has its min and max cardinality and its features,
+/// and a constraint is an object with a single key naming the operator, such as {"implies": [{"feature": "a"}, {"feature": "b"}]}.
+pub fn write<W: Write>(writer: &mut W, feature_model: &FeatureModel) -> serde_json::Result<()> {
+    serde_json::to_writer_pretty(writer, feature_model)
+}
+
+/// Parses a feature model written by [write], recomputing the cached configuration counts.
+pub fn parse(input: &str) -> serde_json::Result<FeatureModel> {
+    serde_json::from_str(input)
+}
+
+#[derive(Serialize)]
+struct FeatureModelRef<'a> {
+    schema_version: u32,
+    root: &'a Feature,
+    constraints: &'a [CrossTreeConstraint],
+}
+
+#[derive(Deserialize)]
+struct FeatureModelData {
+    schema_version: u32,
+    root: Feature,
+    #[serde(default)]
+    constraints: Vec<CrossTreeConstraint>,
+}
+
+impl Serialize for FeatureModel {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        FeatureModelRef {
+            schema_version: SCHEMA_VERSION,
+            root: &self.root_feature,
+            constraints: &self.cross_tree_constraints,
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for FeatureModel {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = FeatureModelData::deserialize(deserializer)?;
+        if data.schema_version != SCHEMA_VERSION {
+            return Err(serde::de::Error::custom(format!("Unsupported schema version {}, expected {SCHEMA_VERSION}", data.schema_version)));
+        }
+
+        Ok(FeatureModel::new(data.root, data.constraints))
+    }
+}
@@ -1,12 +1,35 @@
-use crate::{cross_tree_constraint::CrossTreeConstraint, feature::Feature};
+use num_bigint::BigUint;
+
+use crate::{cross_tree_constraint::CrossTreeConstraint, engine::Engine, feature::Feature};
 
 pub mod feature;
+pub mod attribute;
 pub mod group;
 pub mod cross_tree_constraint;
 pub mod uvl;
+pub mod json;
+pub mod dimacs;
+pub mod featureide;
 pub mod indent;
+pub mod bdd;
+pub mod engine;
+pub mod count;
+pub mod edit;
+pub mod render;
+pub mod diff;
+pub mod propagation;
+pub mod explain;
+pub mod sample;
+pub mod covering_array;
+pub mod metrics;
+pub mod csvconf;
+pub mod simplify;
+pub mod slice;
+
+mod xml;
 
 /// Stores a root feature and a collection of cross tree constraints.
+#[derive(Clone)]
 pub struct FeatureModel {
     pub root_feature: Feature,
     pub cross_tree_constraints: Vec<CrossTreeConstraint>,
@@ -20,4 +43,44 @@ impl FeatureModel {
     pub fn count_features(&self) -> usize {
         self.root_feature.count_features()
     }
-}
\ No newline at end of file
+
+    /// Counts the exact number of configurations, taking cross-tree constraints into account.
+    /// For the count of the tree alone, see the config_count of the root feature.
+    pub fn count_configurations(&self) -> BigUint {
+        Engine::new(self).configurations_number()
+    }
+
+    /// Features which are selected in every valid configuration.
+    pub fn core_features(&self) -> Vec<String> {
+        owned(Engine::new(self).core_features())
+    }
+
+    /// Features which can't be selected in any valid configuration.
+    pub fn dead_features(&self) -> Vec<String> {
+        owned(Engine::new(self).dead_features())
+    }
+
+    /// Features which aren't mandatory, but are selected whenever their parent is selected.
+    pub fn false_optional_features(&self) -> Vec<String> {
+        owned(Engine::new(self).false_optional_features(self))
+    }
+
+    /// Sets of features which are either all selected or all deselected in every valid configuration.
+    /// See [Engine::atomic_sets].
+    pub fn atomic_sets(&self) -> Vec<Vec<String>> {
+        Engine::new(self).atomic_sets()
+            .into_iter()
+            .map(owned)
+            .collect()
+    }
+
+    /// Projects the configurations onto the given features and the root feature.
+    /// See [slice::slice].
+    pub fn slice(&self, features: &[&str]) -> Result<FeatureModel, slice::SliceError> {
+        slice::slice(self, features)
+    }
+}
+
+fn owned(features: Vec<&str>) -> Vec<String> {
+    features.into_iter().map(str::to_owned).collect()
+}
@@ -0,0 +1,265 @@
+use std::{collections::BTreeMap, path::{Path, PathBuf}};
+
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use feature_model::{FeatureModel, covering_array, csvconf, diff, engine::Engine, explain::Explainer, featureide, json, propagation::Propagator, sample::{self, Sampler}, simplify::{self, Pass}, uvl};
+use rand::{SeedableRng, rngs::StdRng};
+
+/// Tools for inspecting and comparing feature models.
+/// Models are read as FeatureIDE XML if the path ends with .xml, as JSON if it ends with .json, and as UVL otherwise.
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Reports the structural and semantic differences between two feature models.
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+    },
+    /// Finds the features which are forced by selecting or deselecting some features,
+    /// along with the reason for each decision.
+    Propagate {
+        model: PathBuf,
+        /// Features to select, or to deselect when prefixed with '!'.
+        assignment: Vec<String>,
+        /// Only shows the chain of decisions leading to the decision of this feature.
+        #[arg(long)]
+        explain: Option<String>,
+    },
+    /// Finds a minimal set of relations of the feature model which reject a configuration.
+    Explain {
+        model: PathBuf,
+        /// Features to select, or to deselect when prefixed with '!'.
+        assignment: Vec<String>,
+    },
+    /// Draws random valid configurations, and writes them to a directory as .csvconf files
+    /// named sample-<index>@0.0.0.csvconf, so they can be read like scraped configurations.
+    Sample {
+        model: PathBuf,
+        output: PathBuf,
+        #[arg(short, long, default_value_t = 10)]
+        count: usize,
+        #[arg(short, long, default_value_t = 0)]
+        seed: u64,
+        /// A directory of .csvconf files, where the frequency of each feature is used as its probability of being selected.
+        /// Without it, every valid configuration is equally likely.
+        #[arg(short, long, default_value = None)]
+        weights: Option<PathBuf>,
+    },
+    /// Generates a small set of valid configurations which covers every valid t-wise interaction of the features,
+    /// and writes them to a directory as .csvconf files named cover-<index>@0.0.0.csvconf.
+    Cover {
+        model: PathBuf,
+        output: PathBuf,
+        /// The number of features in each interaction, between 1 and 3.
+        #[arg(short = 't', long, default_value_t = 2)]
+        strength: usize,
+        /// A directory of .csvconf files, whose most frequent configurations are always included.
+        #[arg(short, long, default_value = None)]
+        configurations: Option<PathBuf>,
+        /// The number of most frequent configurations to include.
+        #[arg(short, long, default_value_t = 10)]
+        seeds: usize,
+        /// Seeds the order in which interactions are packed into configurations.
+        #[arg(short = 'r', long, default_value_t = 0)]
+        random_seed: u64,
+    },
+    /// Lists the core, dead and false-optional features, and the atomic sets with more than one feature.
+    Properties {
+        model: PathBuf,
+    },
+    /// Rewrites a feature model into a simpler model with the same configurations.
+    /// The model is written in the format given by the extension of the output, like models are read.
+    Simplify {
+        model: PathBuf,
+        output: PathBuf,
+        /// The passes to apply: collapse-abstract, merge-groups, lift-alternatives, drop-implied-constraints or drop-redundant-constraints.
+        /// Without any, all passes are applied.
+        #[arg(short, long)]
+        pass: Vec<Pass>,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    match args.command {
+        Command::Diff { old, new } => {
+            let old = read_feature_model(&old)?;
+            let new = read_feature_model(&new)?;
+            print!("{}", diff::diff(&old, &new));
+        }
+        Command::Propagate { model, assignment, explain } => {
+            let feature_model = read_feature_model(&model)?;
+            let propagation = Propagator::new(&feature_model).propagate(parse_assignment(&assignment))?;
+            if !propagation.satisfiable {
+                println!("The assignment can't be extended into a valid configuration");
+            }
+
+            let decisions = match &explain {
+                Some(feature) => propagation.explain(feature),
+                None => propagation.decisions.iter().collect(),
+            };
+            for decision in decisions {
+                println!("{decision}");
+            }
+        }
+        Command::Explain { model, assignment } => {
+            let feature_model = read_feature_model(&model)?;
+            match Explainer::new(&feature_model).explain(parse_assignment(&assignment)) {
+                Some(relations) => {
+                    println!("The configuration is rejected by:");
+                    for relation in relations {
+                        println!("{relation}");
+                    }
+                }
+                None => println!("The configuration is accepted"),
+            }
+        }
+        Command::Sample { model, output, count, seed, weights } => {
+            let feature_model = read_feature_model(&model)?;
+            let sampler = match weights {
+                Some(path) => Sampler::weighted(&feature_model, &sample::frequencies(&read_configurations(&path)?)),
+                None => Sampler::uniform(&feature_model),
+            };
+
+            std::fs::create_dir_all(&output)
+                .with_context(|| format!("Failed to create directory {output:?}"))?;
+
+            let mut rng = StdRng::seed_from_u64(seed);
+            for i in 0..count {
+                let configuration = sampler.sample(&mut rng)
+                    .with_context(|| format!("{model:?} has no valid configurations to sample"))?;
+                let path = output.join(format!("sample-{i}@0.0.0.csvconf"));
+                let mut content = vec![];
+                csvconf::write(&mut content, &feature_model, &configuration)?;
+                std::fs::write(&path, content)
+                    .with_context(|| format!("Failed to write configuration to {path:?}"))?;
+            }
+        }
+        Command::Cover { model, output, strength, configurations, seeds, random_seed } => {
+            let feature_model = read_feature_model(&model)?;
+            let seeds = match configurations {
+                Some(path) => covering_array::most_frequent(&read_configurations(&path)?, seeds),
+                None => vec![],
+            };
+
+            let array = covering_array::covering_array(&feature_model, strength, &seeds, &mut StdRng::seed_from_u64(random_seed))?;
+
+            std::fs::create_dir_all(&output)
+                .with_context(|| format!("Failed to create directory {output:?}"))?;
+
+            for (i, configuration) in array.configurations.iter().enumerate() {
+                let path = output.join(format!("cover-{i}@0.0.0.csvconf"));
+                let mut content = vec![];
+                csvconf::write(&mut content, &feature_model, configuration)?;
+                std::fs::write(&path, content)
+                    .with_context(|| format!("Failed to write configuration to {path:?}"))?;
+            }
+
+            if !seeds.is_empty() {
+                let seed_coverage = covering_array::coverage(&feature_model, strength, &seeds)?;
+                println!("{} of {} seed configurations are valid, and cover {seed_coverage}", array.seeded, seeds.len());
+            }
+            println!("{} configurations cover {}", array.configurations.len(), array.coverage);
+        }
+        Command::Properties { model } => {
+            let feature_model = read_feature_model(&model)?;
+            let mut engine = Engine::new(&feature_model);
+            println!("Core features: {}", engine.core_features().join(", "));
+            println!("Dead features: {}", engine.dead_features().join(", "));
+            println!("False-optional features: {}", engine.false_optional_features(&feature_model).join(", "));
+            println!("Atomic sets:");
+            for set in engine.atomic_sets().into_iter().filter(|set| set.len() > 1) {
+                println!("{}", set.join(", "));
+            }
+        }
+        Command::Simplify { model, output, pass } => {
+            let mut feature_model = read_feature_model(&model)?;
+            let passes = if pass.is_empty() { Pass::ALL.to_vec() } else { pass };
+            let report = simplify::simplify(&mut feature_model, &passes)?;
+            write_feature_model(&output, &feature_model)?;
+
+            println!("Removed {} abstract features: {}", report.removed_features.len(), report.removed_features.join(", "));
+            println!("Merged {} groups", report.merged_groups);
+            println!("Removed {} empty groups", report.removed_groups);
+            println!("Dropped {} redundant constraints:", report.dropped_constraints.len());
+            for constraint in &report.dropped_constraints {
+                println!("{constraint}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses features to select, or to deselect when prefixed with '!'.
+fn parse_assignment(assignment: &[String]) -> impl Iterator<Item = (&str, bool)> {
+    assignment.iter()
+        .map(|a| match a.strip_prefix('!') {
+            Some(feature) => (feature, false),
+            None => (a.as_str(), true),
+        })
+}
+
+fn read_feature_model(path: &Path) -> anyhow::Result<FeatureModel> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {path:?}"))?;
+
+    let feature_model = if has_extension(path, "xml") {
+        featureide::parse(&content)
+            .with_context(|| format!("Failed to parse {path:?} as FeatureIDE XML"))?
+    } else if has_extension(path, "json") {
+        json::parse(&content)
+            .with_context(|| format!("Failed to parse {path:?} as JSON"))?
+    } else {
+        uvl::parse(&content)
+            .with_context(|| format!("Failed to parse {path:?} as UVL"))?
+    };
+
+    Ok(feature_model)
+}
+
+fn write_feature_model(path: &Path, feature_model: &FeatureModel) -> anyhow::Result<()> {
+    let mut content = vec![];
+    if has_extension(path, "xml") {
+        featureide::write(&mut content, feature_model)?;
+    } else if has_extension(path, "json") {
+        json::write(&mut content, feature_model)?;
+    } else {
+        uvl::write(&mut content, feature_model)?;
+    }
+
+    std::fs::write(path, content)
+        .with_context(|| format!("Failed to write feature model to {path:?}"))
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case(extension))
+}
+
+/// Reads every .csvconf file in a directory.
+fn read_configurations(path: &Path) -> anyhow::Result<Vec<BTreeMap<String, bool>>> {
+    let mut paths = std::fs::read_dir(path)
+        .with_context(|| format!("Failed to read directory {path:?}"))?
+        .map(|entry| entry.map(|e| e.path()).with_context(|| format!("Failed to get entry in {path:?}")))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    paths.retain(|path| path.extension().is_some_and(|e| e == "csvconf"));
+
+    paths.into_iter()
+        .map(|path| {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read configuration file at {path:?}"))?;
+            let configuration = cargo_toml::csvconf::parse(&content)
+                .with_context(|| format!("Failed to parse configuration file at {path:?}"))?;
+            Ok(configuration.into_iter()
+                .map(|(feature, enabled)| (feature.to_owned(), enabled))
+                .collect())
+        })
+        .collect()
+}
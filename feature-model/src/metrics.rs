@@ -0,0 +1,95 @@
+use std::collections::HashSet;
+
+use crate::{FeatureModel, feature::Feature, group::GroupKind};
+
+/// Structural metrics of a feature model, following the metrics for software product line feature models
+/// surveyed by Bagheri and Gasevic in "Assessing the maintainability of software product line feature models using structural metrics".
+pub struct Metrics {
+    /// The number of features in the tree, including the root.
+    pub features: usize,
+    /// The number of features without children.
+    pub leaves: usize,
+    pub abstract_features: usize,
+    /// The fraction of the features which are abstract.
+    pub abstract_ratio: f64,
+    /// The number of edges on the longest path from the root to a leaf.
+    pub depth: usize,
+    /// The average number of children of the features which have children.
+    pub branching_factor: f64,
+    pub mandatory_groups: usize,
+    pub optional_groups: usize,
+    pub or_groups: usize,
+    pub alternative_groups: usize,
+    /// The number of groups with any other cardinality.
+    pub cardinality_groups: usize,
+    pub cross_tree_constraints: usize,
+    /// The number of distinct features which are mentioned in cross-tree constraints.
+    pub constraint_variables: usize,
+    /// The number of distinct features mentioned in cross-tree constraints divided by the number of features.
+    pub cross_tree_constraint_ratio: f64,
+    /// The number of distinct cycles introduced into the tree by cross-tree constraints,
+    /// which is the number of cross-tree constraints.
+    pub cyclomatic_complexity: usize,
+}
+
+impl Metrics {
+    pub fn new(feature_model: &FeatureModel) -> Metrics {
+        let features = feature_model.features().count();
+        let parents = feature_model.features()
+            .filter(|f| f.groups.iter().any(|g| !g.features.is_empty()))
+            .count();
+        let children = feature_model.features()
+            .flat_map(|f| f.groups.iter())
+            .map(|g| g.features.len())
+            .sum::<usize>();
+        let abstract_features = feature_model.features()
+            .filter(|f| f.is_abstract)
+            .count();
+
+        let kinds = feature_model.features()
+            .flat_map(|f| f.groups.iter())
+            .map(|g| g.kind())
+            .collect::<Vec<_>>();
+        let count_kind = |matches: fn(&GroupKind) -> bool| kinds.iter().filter(|k| matches(k)).count();
+
+        let constraint_variables = feature_model.cross_tree_constraints.iter()
+            .flat_map(|c| c.features())
+            .collect::<HashSet<_>>()
+            .len();
+
+        Metrics {
+            features,
+            leaves: features - parents,
+            abstract_features,
+            abstract_ratio: ratio(abstract_features, features),
+            depth: depth(&feature_model.root_feature),
+            branching_factor: ratio(children, parents),
+            mandatory_groups: count_kind(|k| *k == GroupKind::Mandatory),
+            optional_groups: count_kind(|k| *k == GroupKind::Optional),
+            or_groups: count_kind(|k| *k == GroupKind::Or),
+            alternative_groups: count_kind(|k| *k == GroupKind::Alternative),
+            cardinality_groups: count_kind(|k| matches!(k, GroupKind::Cardinality(_, _))),
+            cross_tree_constraints: feature_model.cross_tree_constraints.len(),
+            constraint_variables,
+            cross_tree_constraint_ratio: ratio(constraint_variables, features),
+            cyclomatic_complexity: feature_model.cross_tree_constraints.len(),
+        }
+    }
+}
+
+fn depth(feature: &Feature) -> usize {
+    feature.groups.iter()
+        .flat_map(|g| g.features.iter())
+        .map(|child| depth(child) + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Divides the counts, where the ratio of nothing is 0.
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
@@ -0,0 +1,367 @@
+use std::{collections::{BTreeSet, HashMap}, fmt::Display};
+
+use crate::{FeatureModel, bdd::NodeId, cross_tree_constraint::CrossTreeConstraint, engine::Engine, group::Group};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PropagationError {
+    #[error("The feature '{0}' doesn't exist in the feature model")]
+    UnknownFeature(String),
+}
+
+/// The result of propagating a partial configuration.
+pub struct Propagation {
+    /// Determines if the partial configuration can be extended into a valid configuration.
+    pub satisfiable: bool,
+    /// The decisions which are forced by the partial configuration, in the order they were derived.
+    /// The premises of a decision are either part of the partial configuration, or an earlier decision.
+    pub decisions: Vec<Decision>,
+}
+
+/// A feature which is forced to be selected or deselected.
+pub struct Decision {
+    pub feature: String,
+    pub selected: bool,
+    pub reason: Reason,
+}
+
+/// The relation of the feature model which forces a decision.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// The root feature is selected in every configuration.
+    Root,
+    /// A selected feature selects its parent.
+    SelectedChild(String),
+    /// A deselected feature deselects its children.
+    DeselectedParent(String),
+    /// The group of a selected parent already has its maximum number of selected features.
+    GroupMaximum {
+        parent: String,
+        selected: Vec<String>,
+    },
+    /// The group of a selected parent needs all of its undecided features to reach its minimum.
+    GroupMinimum {
+        parent: String,
+        deselected: Vec<String>,
+    },
+    /// The constraint can only be satisfied by the decision, given the decisions of the premises.
+    Constraint {
+        constraint: CrossTreeConstraint,
+        premises: Vec<String>,
+    },
+    /// The decision follows from several relations of the feature model together,
+    /// and was found by checking every valid configuration.
+    Model,
+}
+
+impl Reason {
+    /// The features whose decisions the reason depends on.
+    pub fn premises(&self) -> Vec<&str> {
+        match self {
+            Reason::Root | Reason::Model => vec![],
+            Reason::SelectedChild(feature) | Reason::DeselectedParent(feature) => vec![feature],
+            Reason::GroupMaximum { parent, selected: others }
+            | Reason::GroupMinimum { parent, deselected: others } => std::iter::once(parent)
+                .chain(others)
+                .map(|f| f.as_str())
+                .collect(),
+            Reason::Constraint { premises, .. } => premises.iter().map(|f| f.as_str()).collect(),
+        }
+    }
+}
+
+impl Propagation {
+    /// The chain of decisions which lead to the decision of a feature, ending with the decision itself.
+    /// Returns an empty chain if the feature isn't decided by the propagation.
+    pub fn explain(&self, feature: &str) -> Vec<&Decision> {
+        let indices = self.decisions.iter()
+            .enumerate()
+            .map(|(i, decision)| (decision.feature.as_str(), i))
+            .collect::<HashMap<_, _>>();
+
+        let mut chain = BTreeSet::new();
+        let mut stack = indices.get(feature).into_iter().copied().collect::<Vec<_>>();
+        while let Some(i) = stack.pop() {
+            if chain.insert(i) {
+                stack.extend(self.decisions[i].reason.premises().into_iter().filter_map(|f| indices.get(f)));
+            }
+        }
+
+        chain.into_iter()
+            .map(|i| &self.decisions[i])
+            .collect()
+    }
+}
+
+/// Propagates partial configurations of a feature model.
+///
+/// Decisions are first derived from the individual relations of the model:
+/// parents and children, group cardinalities and cross-tree constraints.
+/// Because these relations can force decisions together which none of them force alone,
+/// the remaining decisions and the satisfiability are then decided exactly using a [crate::bdd::Bdd].
+pub struct Propagator<'a> {
+    feature_model: &'a FeatureModel,
+    engine: Engine,
+    groups: Vec<(&'a str, &'a Group)>,
+}
+
+impl<'a> Propagator<'a> {
+    pub fn new(feature_model: &'a FeatureModel) -> Propagator<'a> {
+        let groups = feature_model.features()
+            .flat_map(|parent| parent.groups.iter().map(|group| (parent.name.as_str(), group)))
+            .collect();
+
+        Propagator { feature_model, engine: Engine::new(feature_model), groups }
+    }
+
+    /// Finds the decisions forced by the partial configuration.
+    /// Features which aren't part of the partial configuration are undecided.
+    pub fn propagate<'b>(&mut self, assignment: impl IntoIterator<Item = (&'b str, bool)>) -> Result<Propagation, PropagationError> {
+        let mut state = State::default();
+        for (feature, selected) in assignment {
+            if !self.engine.variables.contains_key(feature) {
+                return Err(PropagationError::UnknownFeature(feature.to_owned()));
+            }
+            if state.values.insert(feature.to_owned(), selected) == Some(!selected) {
+                state.conflict = true;
+            }
+        }
+
+        let root = &self.feature_model.root_feature.name;
+        state.decide(root, true, Reason::Root);
+
+        while !state.conflict {
+            let changed_groups = self.propagate_groups(&mut state);
+            let changed_constraints = self.propagate_constraints(&mut state);
+            if !changed_groups && !changed_constraints {
+                break;
+            }
+        }
+
+        let satisfiable = !state.conflict && self.propagate_model(&mut state);
+        Ok(Propagation { satisfiable, decisions: state.decisions })
+    }
+
+    /// Applies the relations between parents and children, and the cardinalities of groups.
+    /// Returns true if any decision was made.
+    fn propagate_groups(&self, state: &mut State) -> bool {
+        let mut changed = false;
+
+        for &(parent, group) in &self.groups {
+            let children = group.features.iter().map(|f| f.name.as_str());
+
+            for child in children.clone() {
+                if state.value(child) == Some(true) {
+                    changed |= state.decide(parent, true, Reason::SelectedChild(child.to_owned()));
+                }
+            }
+
+            match state.value(parent) {
+                Some(false) => {
+                    for child in children {
+                        changed |= state.decide(child, false, Reason::DeselectedParent(parent.to_owned()));
+                    }
+                }
+                Some(true) => {
+                    let (selected, rest): (Vec<_>, Vec<_>) = children.partition(|&c| state.value(c) == Some(true));
+                    let (deselected, undecided): (Vec<_>, Vec<_>) = rest.into_iter().partition(|&c| state.value(c) == Some(false));
+
+                    if selected.len() > group.max || selected.len() + undecided.len() < group.min {
+                        state.conflict = true;
+                        return changed;
+                    }
+
+                    let forced = if selected.len() == group.max {
+                        Some((false, Reason::GroupMaximum { parent: parent.to_owned(), selected: owned(&selected) }))
+                    } else if selected.len() + undecided.len() == group.min {
+                        Some((true, Reason::GroupMinimum { parent: parent.to_owned(), deselected: owned(&deselected) }))
+                    } else {
+                        None
+                    };
+
+                    if let Some((value, reason)) = forced {
+                        for child in undecided {
+                            changed |= state.decide(child, value, reason.clone());
+                        }
+                    }
+                }
+                None => {}
+            }
+        }
+
+        changed
+    }
+
+    /// Decides the features of constraints which can only be satisfied by one value of the feature.
+    /// Returns true if any decision was made.
+    fn propagate_constraints(&self, state: &mut State) -> bool {
+        let mut changed = false;
+
+        for constraint in &self.feature_model.cross_tree_constraints {
+            match evaluate(constraint, &|f| state.value(f)) {
+                Some(true) => continue,
+                Some(false) => {
+                    state.conflict = true;
+                    return changed;
+                }
+                None => {}
+            }
+
+            let features = constraint.features().into_iter().collect::<BTreeSet<_>>();
+            let (decided, undecided): (Vec<_>, Vec<_>) = features.into_iter().partition(|&f| state.value(f).is_some());
+
+            for feature in undecided {
+                let falsifies = |value: bool| {
+                    let lookup = |f: &str| if f == feature { Some(value) } else { state.value(f) };
+                    evaluate(constraint, &lookup) == Some(false)
+                };
+
+                let value = match (falsifies(true), falsifies(false)) {
+                    (true, true) => {
+                        state.conflict = true;
+                        return changed;
+                    }
+                    (true, false) => false,
+                    (false, true) => true,
+                    (false, false) => continue,
+                };
+
+                let reason = Reason::Constraint { constraint: constraint.clone(), premises: owned(&decided) };
+                changed |= state.decide(feature, value, reason);
+            }
+        }
+
+        changed
+    }
+
+    /// Decides the remaining features which are forced by the model as a whole.
+    /// Returns false if the partial configuration can't be extended into a valid configuration.
+    fn propagate_model(&mut self, state: &mut State) -> bool {
+        let undecided = self.engine.features().iter()
+            .filter(|f| state.value(f).is_none())
+            .map(|f| (f.clone(), self.engine.variables[f]))
+            .collect::<Vec<_>>();
+
+        let bdd = &mut self.engine.bdd;
+        let mut node = self.engine.root;
+        for (feature, &value) in &state.values {
+            node = bdd.restrict(node, self.engine.variables[feature], value);
+        }
+
+        if node == NodeId::FALSE {
+            return false;
+        }
+
+        for (feature, variable) in undecided {
+            if bdd.restrict(node, variable, false) == NodeId::FALSE {
+                state.decide(&feature, true, Reason::Model);
+            } else if bdd.restrict(node, variable, true) == NodeId::FALSE {
+                state.decide(&feature, false, Reason::Model);
+            }
+        }
+
+        true
+    }
+}
+
+/// The values of decided features during propagation.
+#[derive(Default)]
+struct State {
+    values: HashMap<String, bool>,
+    decisions: Vec<Decision>,
+    conflict: bool,
+}
+
+impl State {
+    fn value(&self, feature: &str) -> Option<bool> {
+        self.values.get(feature).copied()
+    }
+
+    /// Records a decision if the feature is undecided, and returns true if it was recorded.
+    /// A decision which contradicts an earlier decision is a conflict.
+    fn decide(&mut self, feature: &str, selected: bool, reason: Reason) -> bool {
+        match self.value(feature) {
+            Some(value) => {
+                self.conflict |= value != selected;
+                false
+            }
+            None => {
+                self.values.insert(feature.to_owned(), selected);
+                self.decisions.push(Decision { feature: feature.to_owned(), selected, reason });
+                true
+            }
+        }
+    }
+}
+
+/// Evaluates a constraint with three-valued logic, where undecided features are None.
+fn evaluate(constraint: &CrossTreeConstraint, value: &impl Fn(&str) -> Option<bool>) -> Option<bool> {
+    match constraint {
+        CrossTreeConstraint::Feature(name) => value(name),
+        CrossTreeConstraint::Not(a) => evaluate(a, value).map(|a| !a),
+        CrossTreeConstraint::And(operands) => operands.iter()
+            .map(|o| evaluate(o, value))
+            .try_fold(Some(true), |result, o| match o {
+                Some(false) => None,
+                Some(true) => Some(result),
+                None => Some(None),
+            })
+            .unwrap_or(Some(false)),
+        CrossTreeConstraint::Or(operands) => operands.iter()
+            .map(|o| evaluate(o, value))
+            .try_fold(Some(false), |result, o| match o {
+                Some(true) => None,
+                Some(false) => Some(result),
+                None => Some(None),
+            })
+            .unwrap_or(Some(true)),
+        CrossTreeConstraint::Implies(a, b) => match (evaluate(a, value), evaluate(b, value)) {
+            (Some(false), _) | (_, Some(true)) => Some(true),
+            (Some(true), Some(false)) => Some(false),
+            _ => None,
+        },
+        CrossTreeConstraint::Equiv(a, b) => match (evaluate(a, value), evaluate(b, value)) {
+            (Some(a), Some(b)) => Some(a == b),
+            _ => None,
+        },
+    }
+}
+
+fn owned(features: &[&str]) -> Vec<String> {
+    features.iter().map(|&f| f.to_owned()).collect()
+}
+
+fn quoted(features: &[String]) -> String {
+    features.iter()
+        .map(|f| format!("\"{f}\""))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::Root => write!(f, "it is the root feature"),
+            Reason::SelectedChild(child) => write!(f, "its child \"{child}\" is selected"),
+            Reason::DeselectedParent(parent) => write!(f, "its parent \"{parent}\" is deselected"),
+            Reason::GroupMaximum { parent, selected } => {
+                write!(f, "the group below \"{parent}\" already has its maximum number of features selected: {}", quoted(selected))
+            }
+            Reason::GroupMinimum { parent, deselected } if deselected.is_empty() => {
+                write!(f, "the group below \"{parent}\" needs every feature to be selected")
+            }
+            Reason::GroupMinimum { parent, deselected } => {
+                write!(f, "the group below \"{parent}\" needs it to reach its minimum number of features, since {} is deselected", quoted(deselected))
+            }
+            Reason::Constraint { constraint, premises } if premises.is_empty() => write!(f, "of the constraint {constraint}"),
+            Reason::Constraint { constraint, premises } => write!(f, "of the constraint {constraint}, given {}", quoted(premises)),
+            Reason::Model => write!(f, "the relations of the feature model together require it"),
+        }
+    }
+}
+
+impl Display for Decision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.selected { '+' } else { '-' };
+        write!(f, "{sign} \"{}\", because {}", self.feature, self.reason)
+    }
+}
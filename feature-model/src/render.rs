@@ -0,0 +1,93 @@
+use std::{io::Write, path::Path};
+
+use crate::{FeatureModel, cross_tree_constraint::CrossTreeConstraint};
+
+pub mod dot;
+pub mod svg;
+
+/// The output formats of a feature diagram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Dot,
+    Svg,
+}
+
+impl Format {
+    /// Chooses the format from the extension of a path, defaulting to DOT.
+    pub fn from_path(path: impl AsRef<Path>) -> Format {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("svg") => Format::Svg,
+            _ => Format::Dot,
+        }
+    }
+}
+
+/// Writes the feature model as a feature diagram in the given format.
+pub fn write<W: Write>(writer: &mut W, feature_model: &FeatureModel, format: Format) -> std::io::Result<()> {
+    match format {
+        Format::Dot => dot::write(writer, feature_model),
+        Format::Svg => svg::write(writer, feature_model),
+    }
+}
+
+/// A cross-tree constraint which is drawn as a dashed edge between two features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConstraintEdge<'a> {
+    Requires(&'a str, &'a str),
+    Excludes(&'a str, &'a str),
+}
+
+impl<'a> ConstraintEdge<'a> {
+    /// Recognizes constraints of the form `a => b`, `a => !b` and `!a | !b`.
+    /// Other constraints are listed as text.
+    fn of(constraint: &'a CrossTreeConstraint) -> Option<ConstraintEdge<'a>> {
+        use CrossTreeConstraint::{Feature, Implies, Not, Or};
+
+        match constraint {
+            Implies(a, b) => match (a.as_ref(), b.as_ref()) {
+                (Feature(a), Feature(b)) => Some(ConstraintEdge::Requires(a, b)),
+                (Feature(a), Not(b)) => match b.as_ref() {
+                    Feature(b) => Some(ConstraintEdge::Excludes(a, b)),
+                    _ => None,
+                },
+                _ => None,
+            },
+            Or(operands) => match operands.as_slice() {
+                [Not(a), Not(b)] => match (a.as_ref(), b.as_ref()) {
+                    (Feature(a), Feature(b)) => Some(ConstraintEdge::Excludes(a, b)),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn features(self) -> (&'a str, &'a str) {
+        match self {
+            ConstraintEdge::Requires(a, b) | ConstraintEdge::Excludes(a, b) => (a, b),
+        }
+    }
+}
+
+/// Splits the constraints into those drawn as edges, and those listed as text.
+/// Only constraints between features of the tree can be drawn as edges.
+fn partition_constraints<'a>(feature_model: &'a FeatureModel) -> (Vec<ConstraintEdge<'a>>, Vec<&'a CrossTreeConstraint>) {
+    let mut edges = vec![];
+    let mut listed = vec![];
+
+    for constraint in &feature_model.cross_tree_constraints {
+        let edge = ConstraintEdge::of(constraint)
+            .filter(|edge| {
+                let (a, b) = edge.features();
+                feature_model.root_feature.find(a).is_some() && feature_model.root_feature.find(b).is_some()
+            });
+
+        match edge {
+            Some(edge) => edges.push(edge),
+            None => listed.push(constraint),
+        }
+    }
+
+    (edges, listed)
+}
@@ -0,0 +1,99 @@
+use std::{collections::HashMap, io::Write};
+
+use crate::{FeatureModel, feature::Feature, group::GroupKind, indent::tab, xml::escape};
+
+use super::{ConstraintEdge, partition_constraints};
+
+/// Writes the feature model as a feature diagram in the Graphviz DOT language.
+///
+/// Mandatory and optional features are drawn with a filled or empty circle at the end of their edge.
+/// DOT can't draw arcs between edges, so the edges of or-groups and alternative-groups
+/// go through a filled or empty triangle, and other cardinalities through a [min..max] label.
+/// Abstract features are written in italics.
+/// Constraints of the form `a => b` and `!a | !b` are drawn as dashed edges,
+/// and every other constraint is listed in a note.
+pub fn write<W: Write>(writer: &mut W, feature_model: &FeatureModel) -> std::io::Result<()> {
+    let ids = feature_model.root_feature.iter()
+        .enumerate()
+        .map(|(i, feature)| (feature.name.as_str(), i))
+        .collect::<HashMap<_, _>>();
+
+    writeln!(writer, "digraph FeatureModel {{")?;
+    tab(writer, 1)?;
+    writeln!(writer, "node [shape=box, fontname=\"Helvetica\"];")?;
+    tab(writer, 1)?;
+    writeln!(writer, "edge [arrowhead=none];")?;
+
+    let mut group_count = 0;
+    write_feature(writer, &feature_model.root_feature, &ids, &mut group_count)?;
+
+    let (edges, listed) = partition_constraints(feature_model);
+    for edge in edges {
+        tab(writer, 1)?;
+        match edge {
+            ConstraintEdge::Requires(a, b) => writeln!(writer, "f{} -> f{} [style=dashed, constraint=false, arrowhead=normal, label=\"requires\"];", ids[a], ids[b])?,
+            ConstraintEdge::Excludes(a, b) => writeln!(writer, "f{} -> f{} [style=dashed, constraint=false, dir=both, arrowhead=normal, arrowtail=normal, label=\"excludes\"];", ids[a], ids[b])?,
+        }
+    }
+
+    if !listed.is_empty() {
+        let label = listed.iter()
+            .map(|constraint| format!("{}<br align=\"left\"/>", escape(&constraint.to_string())))
+            .collect::<String>();
+        tab(writer, 1)?;
+        writeln!(writer, "constraints [shape=note, label=<{label}>];")?;
+    }
+
+    writeln!(writer, "}}")
+}
+
+fn write_feature<W: Write>(writer: &mut W, feature: &Feature, ids: &HashMap<&str, usize>, group_count: &mut usize) -> std::io::Result<()> {
+    let id = ids[feature.name.as_str()];
+    let name = escape(&feature.name);
+
+    tab(writer, 1)?;
+    if feature.is_abstract {
+        writeln!(writer, "f{id} [label=<<i>{name}</i>>];")?;
+    } else {
+        writeln!(writer, "f{id} [label=<{name}>];")?;
+    }
+
+    for group in &feature.groups {
+        let kind = group.kind();
+        let group_node = match kind {
+            GroupKind::Mandatory | GroupKind::Optional => None,
+            GroupKind::Or => Some("shape=triangle, style=filled, fillcolor=black, label=\"\", fixedsize=true, width=0.2, height=0.15".to_owned()),
+            GroupKind::Alternative => Some("shape=triangle, label=\"\", fixedsize=true, width=0.2, height=0.15".to_owned()),
+            GroupKind::Cardinality(min, max) => Some(format!("shape=plaintext, label=\"[{min}..{max}]\"")),
+        };
+
+        let source = match group_node {
+            Some(attributes) => {
+                let group_id = format!("g{group_count}");
+                *group_count += 1;
+                tab(writer, 1)?;
+                writeln!(writer, "{group_id} [{attributes}];")?;
+                tab(writer, 1)?;
+                writeln!(writer, "f{id} -> {group_id};")?;
+                group_id
+            }
+            None => format!("f{id}"),
+        };
+
+        for child in &group.features {
+            let child_id = ids[child.name.as_str()];
+            tab(writer, 1)?;
+            match kind {
+                GroupKind::Mandatory => writeln!(writer, "{source} -> f{child_id} [arrowhead=dot];")?,
+                GroupKind::Optional => writeln!(writer, "{source} -> f{child_id} [arrowhead=odot];")?,
+                _ => writeln!(writer, "{source} -> f{child_id};")?,
+            }
+        }
+
+        for child in &group.features {
+            write_feature(writer, child, ids, group_count)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,224 @@
+use std::{collections::HashMap, io::Write};
+
+use crate::{FeatureModel, feature::Feature, group::GroupKind, indent::tab, xml::escape};
+
+use super::{ConstraintEdge, partition_constraints};
+
+const FONT_SIZE: f64 = 12.0;
+/// Estimated average width of a character, since the real width depends on the font of the viewer.
+const CHARACTER_WIDTH: f64 = 7.0;
+const BOX_HEIGHT: f64 = 24.0;
+const BOX_PADDING: f64 = 8.0;
+const HORIZONTAL_GAP: f64 = 16.0;
+const LEVEL_HEIGHT: f64 = 72.0;
+const MARGIN: f64 = 16.0;
+const CIRCLE_RADIUS: f64 = 4.0;
+const ARC_RADIUS: f64 = 16.0;
+const LINE_HEIGHT: f64 = 16.0;
+
+/// Writes the feature model as a feature diagram in SVG.
+///
+/// The tree is drawn top-down, where every feature is centered above its subtree.
+/// Mandatory and optional features are marked with a filled or empty circle,
+/// the edges of or-groups and alternative-groups are connected by a filled or empty arc,
+/// and other cardinalities get an empty arc with a [min..max] label.
+/// Abstract features are written in italics.
+/// Constraints of the form `a => b` and `!a | !b` are drawn as dashed edges,
+/// and every other constraint is listed below the diagram.
+pub fn write<W: Write>(writer: &mut W, feature_model: &FeatureModel) -> std::io::Result<()> {
+    let layout = Layout::new(&feature_model.root_feature);
+
+    let (edges, listed) = partition_constraints(feature_model);
+
+    let tree_height = layout.depth as f64 * LEVEL_HEIGHT + BOX_HEIGHT;
+    let edge_space = if edges.is_empty() { 0.0 } else { LEVEL_HEIGHT / 2.0 };
+    let list_space = if listed.is_empty() { 0.0 } else { (listed.len() + 1) as f64 * LINE_HEIGHT + MARGIN };
+    let list_width = listed.iter()
+        .map(|constraint| constraint.to_string().chars().count() as f64 * CHARACTER_WIDTH)
+        .fold(0.0, f64::max);
+    let width = layout.width.max(list_width) + 2.0 * MARGIN;
+    let height = tree_height + edge_space + list_space + 2.0 * MARGIN;
+
+    writeln!(writer, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.1}\" height=\"{height:.1}\" viewBox=\"0 0 {width:.1} {height:.1}\" font-family=\"Helvetica, Arial, sans-serif\" font-size=\"{FONT_SIZE}\">")?;
+    tab(writer, 1)?;
+    writeln!(writer, "<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"8\" markerHeight=\"8\" orient=\"auto-start-reverse\"><path d=\"M 0 0 L 10 5 L 0 10 z\"/></marker></defs>")?;
+    tab(writer, 1)?;
+    writeln!(writer, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>")?;
+
+    for node in &layout.nodes {
+        write_groups(writer, node, &layout)?;
+    }
+
+    for node in &layout.nodes {
+        write_node(writer, node)?;
+    }
+
+    for &edge in &edges {
+        write_constraint_edge(writer, edge, &layout)?;
+    }
+
+    if !listed.is_empty() {
+        let mut y = MARGIN + tree_height + edge_space + MARGIN + LINE_HEIGHT;
+        tab(writer, 1)?;
+        writeln!(writer, "<text x=\"{MARGIN:.1}\" y=\"{y:.1}\" font-weight=\"bold\">Constraints</text>")?;
+        for constraint in &listed {
+            y += LINE_HEIGHT;
+            tab(writer, 1)?;
+            writeln!(writer, "<text x=\"{MARGIN:.1}\" y=\"{y:.1}\">{}</text>", escape(&constraint.to_string()))?;
+        }
+    }
+
+    writeln!(writer, "</svg>")
+}
+
+/// A feature placed in the diagram, where x is the center and y is the top of its box.
+struct Node<'a> {
+    feature: &'a Feature,
+    x: f64,
+    y: f64,
+    width: f64,
+}
+
+/// Positions of the features of a tree, with the root at the top.
+struct Layout<'a> {
+    nodes: Vec<Node<'a>>,
+    positions: HashMap<&'a str, usize>,
+    width: f64,
+    depth: usize,
+}
+
+impl<'a> Layout<'a> {
+    fn new(root: &'a Feature) -> Layout<'a> {
+        let mut layout = Layout { nodes: vec![], positions: HashMap::new(), width: 0.0, depth: 0 };
+        layout.width = layout.place(root, MARGIN, 0);
+        layout
+    }
+
+    /// Places the feature centered above its subtree, starting at the left coordinate,
+    /// and returns the width of the subtree.
+    fn place(&mut self, feature: &'a Feature, left: f64, depth: usize) -> f64 {
+        let box_width = box_width(feature);
+        let children_width = children_width(feature);
+        let width = box_width.max(children_width);
+
+        self.positions.insert(&feature.name, self.nodes.len());
+        self.nodes.push(Node {
+            feature,
+            x: left + width / 2.0,
+            y: MARGIN + depth as f64 * LEVEL_HEIGHT,
+            width: box_width,
+        });
+        self.depth = self.depth.max(depth);
+
+        let mut child_left = left + (width - children_width) / 2.0;
+        for child in feature.groups.iter().flat_map(|g| g.features.iter()) {
+            child_left += self.place(child, child_left, depth + 1) + HORIZONTAL_GAP;
+        }
+
+        width
+    }
+
+    fn node(&self, name: &str) -> &Node<'a> {
+        &self.nodes[self.positions[name]]
+    }
+}
+
+fn box_width(feature: &Feature) -> f64 {
+    feature.name.chars().count() as f64 * CHARACTER_WIDTH + 2.0 * BOX_PADDING
+}
+
+fn subtree_width(feature: &Feature) -> f64 {
+    box_width(feature).max(children_width(feature))
+}
+
+fn children_width(feature: &Feature) -> f64 {
+    let children = feature.groups.iter()
+        .flat_map(|g| g.features.iter())
+        .collect::<Vec<_>>();
+    let gaps = children.len().saturating_sub(1) as f64 * HORIZONTAL_GAP;
+    children.into_iter().map(subtree_width).sum::<f64>() + gaps
+}
+
+fn write_node<W: Write>(writer: &mut W, node: &Node) -> std::io::Result<()> {
+    let left = node.x - node.width / 2.0;
+    let center = node.y + BOX_HEIGHT / 2.0;
+    let style = if node.feature.is_abstract { " font-style=\"italic\"" } else { "" };
+
+    tab(writer, 1)?;
+    writeln!(writer, "<rect x=\"{left:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{BOX_HEIGHT:.1}\" fill=\"white\" stroke=\"black\"/>", node.y, node.width)?;
+    tab(writer, 1)?;
+    writeln!(writer, "<text x=\"{:.1}\" y=\"{center:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\"{style}>{}</text>", node.x, escape(&node.feature.name))
+}
+
+/// Writes the edges from a feature to its children, along with the notation of each group.
+fn write_groups<W: Write>(writer: &mut W, node: &Node, layout: &Layout) -> std::io::Result<()> {
+    let (x, bottom) = (node.x, node.y + BOX_HEIGHT);
+
+    for group in &node.feature.groups {
+        let kind = group.kind();
+        let children = group.features.iter()
+            .map(|child| layout.node(&child.name))
+            .collect::<Vec<_>>();
+
+        for child in &children {
+            tab(writer, 1)?;
+            writeln!(writer, "<line x1=\"{x:.1}\" y1=\"{bottom:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"black\"/>", child.x, child.y)?;
+
+            let fill = match kind {
+                GroupKind::Mandatory => "black",
+                GroupKind::Optional => "white",
+                _ => continue,
+            };
+            tab(writer, 1)?;
+            writeln!(writer, "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{CIRCLE_RADIUS:.1}\" fill=\"{fill}\" stroke=\"black\"/>", child.x, child.y - CIRCLE_RADIUS)?;
+        }
+
+        let (Some(first), Some(last)) = (children.first(), children.last()) else {
+            continue;
+        };
+        let point_towards = |child: &Node| {
+            let angle = (child.y - bottom).atan2(child.x - x);
+            (x + ARC_RADIUS * angle.cos(), bottom + ARC_RADIUS * angle.sin())
+        };
+        let (x1, y1) = point_towards(first);
+        let (x2, y2) = point_towards(last);
+        let arc = format!("A {ARC_RADIUS:.1} {ARC_RADIUS:.1} 0 0 0 {x2:.1} {y2:.1}");
+
+        match kind {
+            GroupKind::Mandatory | GroupKind::Optional => {}
+            GroupKind::Or => {
+                tab(writer, 1)?;
+                writeln!(writer, "<path d=\"M {x:.1} {bottom:.1} L {x1:.1} {y1:.1} {arc} Z\" fill=\"black\" stroke=\"black\"/>")?;
+            }
+            GroupKind::Alternative => {
+                tab(writer, 1)?;
+                writeln!(writer, "<path d=\"M {x1:.1} {y1:.1} {arc}\" fill=\"none\" stroke=\"black\"/>")?;
+            }
+            GroupKind::Cardinality(min, max) => {
+                tab(writer, 1)?;
+                writeln!(writer, "<path d=\"M {x1:.1} {y1:.1} {arc}\" fill=\"none\" stroke=\"black\"/>")?;
+                tab(writer, 1)?;
+                writeln!(writer, "<text x=\"{:.1}\" y=\"{y2:.1}\" dominant-baseline=\"hanging\">[{min}..{max}]</text>", x2 + CIRCLE_RADIUS)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a dashed curve between two features, which bends below the lowest of them.
+fn write_constraint_edge<W: Write>(writer: &mut W, edge: ConstraintEdge, layout: &Layout) -> std::io::Result<()> {
+    let (a, b, markers) = match edge {
+        ConstraintEdge::Requires(a, b) => (a, b, "marker-end=\"url(#arrow)\""),
+        ConstraintEdge::Excludes(a, b) => (a, b, "marker-start=\"url(#arrow)\" marker-end=\"url(#arrow)\""),
+    };
+    let a = layout.node(a);
+    let b = layout.node(b);
+    let (x1, y1) = (a.x, a.y + BOX_HEIGHT);
+    let (x2, y2) = (b.x, b.y + BOX_HEIGHT);
+    let control_x = (x1 + x2) / 2.0;
+    let control_y = y1.max(y2) + LEVEL_HEIGHT / 2.0;
+
+    tab(writer, 1)?;
+    writeln!(writer, "<path d=\"M {x1:.1} {y1:.1} Q {control_x:.1} {control_y:.1} {x2:.1} {y2:.1}\" fill=\"none\" stroke=\"gray\" stroke-dasharray=\"4 3\" {markers}/>")
+}
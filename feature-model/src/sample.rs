@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, HashMap};
+
+use rand::Rng;
+
+use crate::{FeatureModel, bdd::NodeId, engine::Engine};
+
+/// Draws random valid configurations of a feature model.
+///
+/// Every feature has a probability of being selected,
+/// and a valid configuration is drawn with a probability proportional to the product of the probabilities of its decisions.
+/// When every probability is 1/2, all valid configurations are equally likely.
+/// The weights of the functions in the [crate::bdd::Bdd] are computed once, in log space so large models don't underflow.
+/// The smallest probability of a decision, so a feature which is never or always selected in the given probabilities
+/// can still take the value which a valid configuration needs, like a core feature with probability 0.
+const MIN_PROBABILITY: f64 = 1e-6;
+
+pub struct Sampler {
+    engine: Engine,
+    probabilities: Vec<f64>,
+    log_weights: HashMap<NodeId, f64>,
+}
+
+impl Sampler {
+    /// A sampler which draws every valid configuration with the same probability.
+    pub fn uniform(feature_model: &FeatureModel) -> Sampler {
+        Sampler::weighted(feature_model, &HashMap::new())
+    }
+
+    /// A sampler which prefers configurations where each feature is selected with the given probability,
+    /// such as its observed usage frequency.
+    /// Features without a probability are selected with probability 1/2,
+    /// and probabilities are kept at least [MIN_PROBABILITY] away from 0 and 1.
+    pub fn weighted(feature_model: &FeatureModel, probabilities: &HashMap<String, f64>) -> Sampler {
+        let engine = Engine::new(feature_model);
+        let probabilities = engine.features().iter()
+            .map(|f| probabilities.get(f).copied().unwrap_or(0.5).clamp(MIN_PROBABILITY, 1.0 - MIN_PROBABILITY))
+            .collect();
+
+        let mut sampler = Sampler { engine, probabilities, log_weights: HashMap::new() };
+        sampler.log_weight(sampler.engine.root);
+        sampler
+    }
+
+    /// Draws a valid configuration, which assigns a value to every feature of the model.
+    /// Returns None if no valid configuration can be drawn.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<BTreeMap<String, bool>> {
+        let root = self.engine.root;
+        if self.cached_log_weight(root) == f64::NEG_INFINITY {
+            return None;
+        }
+
+        // Features which the function doesn't depend on are drawn independently.
+        let mut assignment = self.probabilities.iter()
+            .map(|&p| rng.random_bool(p))
+            .collect::<Vec<_>>();
+
+        let bdd = &self.engine.bdd;
+        let mut node = root;
+        while let Some(variable) = bdd.top_variable(node) {
+            let (low, high) = bdd.children(node);
+            let p = self.probabilities[variable as usize];
+            let high_probability = (p.ln() + self.cached_log_weight(high) - self.cached_log_weight(node)).exp();
+            let selected = rng.random::<f64>() < high_probability;
+            assignment[variable as usize] = selected;
+            node = if selected { high } else { low };
+        }
+
+        Some(self.engine.features().iter()
+            .cloned()
+            .zip(assignment)
+            .collect())
+    }
+
+    /// The log weight of a terminal, or of a node whose weight has already been computed.
+    fn cached_log_weight(&self, f: NodeId) -> f64 {
+        match f {
+            NodeId::FALSE => f64::NEG_INFINITY,
+            NodeId::TRUE => 0.0,
+            _ => self.log_weights[&f],
+        }
+    }
+
+    /// The logarithm of the total probability of the assignments which satisfy f,
+    /// over the variables from the top variable of f and below.
+    fn log_weight(&mut self, f: NodeId) -> f64 {
+        if f.is_terminal() || self.log_weights.contains_key(&f) {
+            return self.cached_log_weight(f);
+        }
+
+        let variable = self.engine.bdd.top_variable(f).expect("Non-terminal nodes have a variable");
+        let (low, high) = self.engine.bdd.children(f);
+        let p = self.probabilities[variable as usize];
+        let low = (1.0 - p).ln() + self.log_weight(low);
+        let high = p.ln() + self.log_weight(high);
+        let weight = log_sum_exp(low, high);
+        self.log_weights.insert(f, weight);
+        weight
+    }
+}
+
+/// The fraction of the configurations which select each feature,
+/// which can be used as the probabilities of a weighted [Sampler].
+pub fn frequencies<'a>(configurations: impl IntoIterator<Item = &'a BTreeMap<String, bool>>) -> HashMap<String, f64> {
+    let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+    for configuration in configurations {
+        for (feature, &enabled) in configuration {
+            let (selected, total) = counts.entry(feature).or_default();
+            *selected += enabled as usize;
+            *total += 1;
+        }
+    }
+
+    counts.into_iter()
+        .map(|(feature, (selected, total))| (feature.to_owned(), selected as f64 / total as f64))
+        .collect()
+}
+
+fn log_sum_exp(a: f64, b: f64) -> f64 {
+    let max = a.max(b);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+
+    max + ((a - max).exp() + (b - max).exp()).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::uvl;
+
+    use super::Sampler;
+
+    #[test]
+    fn samples_core_and_dead_features_with_extreme_frequencies() {
+        let feature_model = uvl::parse("features\n\t\"r\"\n\t\tmandatory\n\t\t\t\"core\"\n\t\toptional\n\t\t\t\"dead\"\nconstraints\n\t!\"dead\"\n").unwrap();
+        let frequencies = HashMap::from([("core".to_owned(), 0.0), ("dead".to_owned(), 1.0)]);
+        let sampler = Sampler::weighted(&feature_model, &frequencies);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..10 {
+            let configuration = sampler.sample(&mut rng).unwrap();
+            assert!(configuration["core"] && !configuration["dead"]);
+        }
+    }
+}
@@ -0,0 +1,372 @@
+use std::{collections::HashSet, fmt::Display, str::FromStr};
+
+use crate::{FeatureModel, bdd::NodeId, cross_tree_constraint::CrossTreeConstraint, engine::Engine, feature::Feature, group::{Group, GroupKind}};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SimplifyError {
+    #[error("The simplified feature model doesn't have the same configurations as the original, so the original is kept")]
+    ChangedConfigurations,
+    #[error("Unknown simplification pass '{0}'")]
+    UnknownPass(String),
+}
+
+/// A rewrite of a feature model which keeps its configurations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pass {
+    /// Removes abstract features with a single child, which are selected exactly when the child is,
+    /// and abstract mandatory features with a single group, whose group is moved to the parent.
+    CollapseAbstract,
+    /// Merges the mandatory groups of a feature into one group, and the optional groups into another.
+    MergeGroups,
+    /// Replaces an abstract feature in a group of at most one feature by the features of its alternative group.
+    LiftAlternatives,
+    /// Removes cross-tree constraints which are already implied by the tree.
+    DropImpliedConstraints,
+    /// Removes cross-tree constraints which are implied by the tree together with the other constraints.
+    DropRedundantConstraints,
+}
+
+impl Pass {
+    pub const ALL: [Pass; 5] = [Pass::CollapseAbstract, Pass::MergeGroups, Pass::LiftAlternatives, Pass::DropImpliedConstraints, Pass::DropRedundantConstraints];
+}
+
+/// What was changed by [simplify].
+#[derive(Debug, Default)]
+pub struct Report {
+    /// The abstract features which were removed, and aren't part of the simplified model.
+    pub removed_features: Vec<String>,
+    /// The number of groups which were merged into another group.
+    pub merged_groups: usize,
+    /// The number of groups without features which were removed.
+    /// Only groups which allow selecting no feature are removed, since the others exclude their parent.
+    pub removed_groups: usize,
+    pub dropped_constraints: Vec<CrossTreeConstraint>,
+}
+
+impl Report {
+    fn changes(&self) -> (usize, usize, usize, usize) {
+        (self.removed_features.len(), self.merged_groups, self.removed_groups, self.dropped_constraints.len())
+    }
+}
+
+/// Applies the passes in order until none of them changes the model anymore.
+///
+/// Afterwards, the simplified model is checked to have the same configurations as the original,
+/// with the removed abstract features left out.
+/// If the check fails, the model is left unchanged.
+pub fn simplify(feature_model: &mut FeatureModel, passes: &[Pass]) -> Result<Report, SimplifyError> {
+    let original = feature_model.clone();
+    let mut report = Report::default();
+
+    loop {
+        let before = report.changes();
+        for pass in passes {
+            apply(feature_model, *pass, &mut report);
+        }
+        if report.changes() == before {
+            break;
+        }
+    }
+
+    if !equivalent(&original, feature_model) {
+        *feature_model = original;
+        return Err(SimplifyError::ChangedConfigurations);
+    }
+
+    Ok(report)
+}
+
+/// Determines if the simplified model has the same configurations as the original, when the features
+/// which only exist in the original are left out, and those features are decided by the remaining features.
+/// A simplified model with features which don't exist in the original is never equivalent.
+pub fn equivalent(original: &FeatureModel, simplified: &FeatureModel) -> bool {
+    let mut engine = Engine::new(original);
+    let original_root = engine.root;
+    let is_unknown = |f: &str| !engine.variables.contains_key(f);
+    if simplified.features().any(|f| is_unknown(&f.name)) || simplified.cross_tree_constraints.iter().flat_map(|c| c.features()).any(is_unknown) {
+        return false;
+    }
+    let simplified_root = engine.compile(simplified);
+
+    let kept = simplified.features()
+        .map(|f| f.name.as_str())
+        .chain(simplified.cross_tree_constraints.iter().flat_map(|c| c.features()))
+        .map(|f| engine.variables[f])
+        .collect::<HashSet<_>>();
+    let removed = engine.features().len() - kept.len();
+
+    // Every configuration of the projection extends to exactly one configuration of the original,
+    // when the original has as many configurations as the projection without the removed features.
+    let projected = engine.bdd.exists(original_root, |v| !kept.contains(&v));
+    projected == simplified_root && engine.bdd.count(projected) == engine.bdd.count(original_root) << removed
+}
+
+fn apply(feature_model: &mut FeatureModel, pass: Pass, report: &mut Report) {
+    let referenced = feature_model.cross_tree_constraints.iter()
+        .flat_map(|c| c.features())
+        .map(str::to_owned)
+        .collect::<HashSet<_>>();
+
+    match pass {
+        Pass::CollapseAbstract => collapse_abstract(&mut feature_model.root_feature, &referenced, &mut report.removed_features, &mut report.removed_groups),
+        Pass::MergeGroups => merge_groups(&mut feature_model.root_feature, &mut report.merged_groups, &mut report.removed_groups),
+        Pass::LiftAlternatives => lift_alternatives(&mut feature_model.root_feature, &referenced, &mut report.removed_features),
+        Pass::DropImpliedConstraints => drop_implied_constraints(feature_model, &mut report.dropped_constraints),
+        Pass::DropRedundantConstraints => drop_redundant_constraints(feature_model, &mut report.dropped_constraints),
+    }
+}
+
+/// An abstract feature with a single group, which no constraint mentions.
+fn is_removable(feature: &Feature, referenced: &HashSet<String>) -> bool {
+    feature.is_abstract && feature.groups.len() == 1 && !referenced.contains(&feature.name)
+}
+
+/// A group without features which allows selecting none of them, so removing it doesn't change the configurations.
+fn is_empty_optional(group: &Group) -> bool {
+    group.features.is_empty() && group.min == 0
+}
+
+fn collapse_abstract(feature: &mut Feature, referenced: &HashSet<String>, removed: &mut Vec<String>, removed_groups: &mut usize) {
+    let mut lifted = vec![];
+    for group in &mut feature.groups {
+        let is_mandatory = group.kind() == GroupKind::Mandatory;
+        let mut kept = vec![];
+        for mut child in std::mem::take(&mut group.features) {
+            collapse_abstract(&mut child, referenced, removed, removed_groups);
+
+            while is_removable(&child, referenced) && child.groups[0].features.len() == 1 && child.groups[0].kind() == GroupKind::Mandatory {
+                removed.push(child.name);
+                child = child.groups.remove(0).features.remove(0);
+            }
+
+            if is_mandatory && is_removable(&child, referenced) {
+                removed.push(child.name);
+                lifted.extend(child.groups);
+            } else {
+                kept.push(child);
+            }
+        }
+
+        *group = if is_mandatory {
+            Group::mandatory(kept)
+        } else {
+            Group::new(kept, group.min, group.max)
+        };
+    }
+
+    let groups = feature.groups.len();
+    feature.groups.retain(|g| !is_empty_optional(g));
+    *removed_groups += groups - feature.groups.len();
+    feature.groups.extend(lifted);
+    feature.update_config_count();
+}
+
+fn merge_groups(feature: &mut Feature, merged: &mut usize, removed_groups: &mut usize) {
+    let mut groups: Vec<Group> = vec![];
+    let mut mandatory = None;
+    let mut optional = None;
+    for mut group in std::mem::take(&mut feature.groups) {
+        for child in &mut group.features {
+            merge_groups(child, merged, removed_groups);
+        }
+
+        let index: &mut Option<usize> = match group.kind() {
+            _ if is_empty_optional(&group) => {
+                *removed_groups += 1;
+                continue;
+            }
+            GroupKind::Mandatory => &mut mandatory,
+            GroupKind::Optional => &mut optional,
+            _ => {
+                groups.push(group);
+                continue;
+            }
+        };
+
+        match *index {
+            Some(i) => {
+                groups[i].features.append(&mut group.features);
+                *merged += 1;
+            }
+            None => {
+                *index = Some(groups.len());
+                groups.push(group);
+            }
+        }
+    }
+
+    for (index, new) in [(mandatory, Group::mandatory as fn(Vec<Feature>) -> Group), (optional, Group::optional)] {
+        if let Some(i) = index {
+            groups[i] = new(std::mem::take(&mut groups[i].features));
+        }
+    }
+
+    feature.groups = groups;
+    feature.update_config_count();
+}
+
+fn lift_alternatives(feature: &mut Feature, referenced: &HashSet<String>, removed: &mut Vec<String>) {
+    for group in &mut feature.groups {
+        let mut features = vec![];
+        for mut child in std::mem::take(&mut group.features) {
+            lift_alternatives(&mut child, referenced, removed);
+
+            // When at most one feature of the group is selected, the abstract feature is selected exactly
+            // when one of its alternatives is, so its alternatives can take its place.
+            if group.max == 1 && is_removable(&child, referenced) && child.groups[0].kind() == GroupKind::Alternative {
+                removed.push(child.name);
+                features.append(&mut child.groups[0].features);
+            } else {
+                features.push(child);
+            }
+        }
+
+        *group = Group::new(features, group.min, group.max);
+    }
+
+    feature.update_config_count();
+}
+
+fn drop_implied_constraints(feature_model: &mut FeatureModel, dropped: &mut Vec<CrossTreeConstraint>) {
+    let mut engine = Engine::new(feature_model);
+    let tree = engine.compile_tree(feature_model);
+    let in_tree = feature_model.features()
+        .map(|f| f.name.clone())
+        .collect::<HashSet<_>>();
+
+    // Constraints on features outside the tree are kept, since the features would disappear with them.
+    let (implied, kept) = std::mem::take(&mut feature_model.cross_tree_constraints).into_iter()
+        .partition::<Vec<_>, _>(|constraint| {
+            if !constraint.features().iter().all(|&f| in_tree.contains(f)) {
+                return false;
+            }
+            let constraint = engine.compile_constraint(constraint);
+            engine.bdd.implies(tree, constraint) == NodeId::TRUE
+        });
+
+    feature_model.cross_tree_constraints = kept;
+    dropped.extend(implied);
+}
+
+/// Removes the constraints one at a time, so of several constraints which imply each other, the first ones are removed.
+pub(crate) fn drop_redundant_constraints(feature_model: &mut FeatureModel, dropped: &mut Vec<CrossTreeConstraint>) {
+    let mut engine = Engine::new(feature_model);
+    let tree = engine.compile_tree(feature_model);
+    let in_tree = feature_model.features()
+        .map(|f| f.name.clone())
+        .collect::<HashSet<_>>();
+
+    let constraints = std::mem::take(&mut feature_model.cross_tree_constraints);
+    let nodes = constraints.iter()
+        .map(|c| engine.compile_constraint(c))
+        .collect::<Vec<_>>();
+
+    // The conjunction of the constraints after each constraint, which haven't been decided on yet.
+    let mut after = vec![NodeId::TRUE; nodes.len() + 1];
+    for i in (0..nodes.len()).rev() {
+        after[i] = engine.bdd.and(nodes[i], after[i + 1]);
+    }
+
+    // The conjunction of the tree and the constraints which are kept so far.
+    let mut before = tree;
+    for (i, constraint) in constraints.into_iter().enumerate() {
+        let rest = engine.bdd.and(before, after[i + 1]);
+        let in_tree = constraint.features().iter().all(|&f| in_tree.contains(f));
+        if in_tree && engine.bdd.implies(rest, nodes[i]) == NodeId::TRUE {
+            dropped.push(constraint);
+        } else {
+            before = engine.bdd.and(before, nodes[i]);
+            feature_model.cross_tree_constraints.push(constraint);
+        }
+    }
+}
+
+impl FromStr for Pass {
+    type Err = SimplifyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "collapse-abstract" => Ok(Pass::CollapseAbstract),
+            "merge-groups" => Ok(Pass::MergeGroups),
+            "lift-alternatives" => Ok(Pass::LiftAlternatives),
+            "drop-implied-constraints" => Ok(Pass::DropImpliedConstraints),
+            "drop-redundant-constraints" => Ok(Pass::DropRedundantConstraints),
+            _ => Err(SimplifyError::UnknownPass(s.to_owned())),
+        }
+    }
+}
+
+impl Display for Pass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pass::CollapseAbstract => write!(f, "collapse-abstract"),
+            Pass::MergeGroups => write!(f, "merge-groups"),
+            Pass::LiftAlternatives => write!(f, "lift-alternatives"),
+            Pass::DropImpliedConstraints => write!(f, "drop-implied-constraints"),
+            Pass::DropRedundantConstraints => write!(f, "drop-redundant-constraints"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FeatureModel, feature::Feature, group::Group, uvl};
+
+    use super::{Pass, equivalent, simplify};
+
+    fn write(feature_model: &FeatureModel) -> String {
+        let mut output = Vec::new();
+        uvl::write(&mut output, feature_model).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn collapses_abstract_features_and_merges_groups() {
+        let mut feature_model = uvl::parse(r#"features
+    "r"
+        mandatory
+            "lifted" {abstract}
+                optional
+                    "a"
+                    "b"
+        optional
+            "c"
+        optional
+            "d"
+        mandatory
+            "single" {abstract}
+                mandatory
+                    "e"
+"#).unwrap();
+
+        let report = simplify(&mut feature_model, &Pass::ALL).unwrap();
+        assert_eq!(report.removed_features, ["lifted", "single"]);
+        assert_eq!(report.merged_groups, 2);
+        assert_eq!(report.removed_groups, 1);
+        assert_eq!(write(&feature_model), "features\n\t\"r\"\n\t\toptional\n\t\t\t\"c\"\n\t\t\t\"d\"\n\t\t\t\"a\"\n\t\t\t\"b\"\n\t\tmandatory\n\t\t\t\"e\"\n");
+    }
+
+    #[test]
+    fn keeps_empty_groups_which_exclude_their_parent() {
+        let excluded = Feature::new("excluded".to_owned(), vec![Group::new(vec![], 1, 0)], false);
+        let free = Feature::new("free".to_owned(), vec![Group::optional(vec![])], false);
+        let root_feature = Feature::new("r".to_owned(), vec![Group::optional(vec![excluded, free])], false);
+        let mut feature_model = FeatureModel::new(root_feature, vec![]);
+
+        let report = simplify(&mut feature_model, &[Pass::CollapseAbstract, Pass::MergeGroups]).unwrap();
+        assert_eq!(report.removed_groups, 1);
+        assert_eq!(feature_model.find_feature("excluded").unwrap().groups().len(), 1);
+        assert!(feature_model.find_feature("free").unwrap().groups().is_empty());
+    }
+
+    #[test]
+    fn models_with_unknown_features_are_not_equivalent() {
+        let original = uvl::parse("features\n\t\"r\"\n\t\toptional\n\t\t\t\"a\"\n").unwrap();
+        let with_feature = uvl::parse("features\n\t\"r\"\n\t\toptional\n\t\t\t\"a\"\n\t\t\t\"b\"\n").unwrap();
+        let with_constraint = uvl::parse("features\n\t\"r\"\n\t\toptional\n\t\t\t\"a\"\nconstraints\n\t\"a\" | !\"b\"\n").unwrap();
+
+        assert!(equivalent(&original, &original));
+        assert!(!equivalent(&original, &with_feature));
+        assert!(!equivalent(&original, &with_constraint));
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+
+use crate::{FeatureModel, bdd::NodeId, cross_tree_constraint::{self, CrossTreeConstraint}, engine::Engine, feature::Feature, group::Group, simplify};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SliceError {
+    #[error("The feature {0} doesn't exist in the tree of the feature model")]
+    UnknownFeature(String),
+    #[error("The feature model has no valid configurations, so it can't be sliced")]
+    VoidModel,
+}
+
+/// Projects the configurations of the feature model onto the given features and the root feature,
+/// by existentially quantifying the other features.
+///
+/// Every feature of the slice is placed below its closest ancestor in the slice.
+/// Groups which lose features are relaxed to a cardinality which allows every projected configuration,
+/// and children of features outside the slice are added to an optional group of their new parent.
+/// The configurations which the relaxed tree allows, but the projection doesn't, are excluded by derived cross-tree constraints,
+/// so the sliced model has exactly the configurations of the original restricted to the slice.
+pub fn slice(feature_model: &FeatureModel, features: &[&str]) -> Result<FeatureModel, SliceError> {
+    let in_tree = feature_model.features()
+        .map(|f| f.name.as_str())
+        .collect::<HashSet<_>>();
+    if let Some(unknown) = features.iter().find(|f| !in_tree.contains(*f)) {
+        return Err(SliceError::UnknownFeature(unknown.to_string()));
+    }
+
+    let kept = features.iter()
+        .copied()
+        .chain(std::iter::once(feature_model.root_feature.name.as_str()))
+        .collect::<HashSet<_>>();
+    let root_feature = slice_feature(&feature_model.root_feature, &kept);
+    let mut sliced = FeatureModel::new(root_feature, vec![]);
+
+    let mut engine = Engine::new(feature_model);
+    let kept_variables = kept.iter()
+        .map(|f| engine.variables[*f])
+        .collect::<HashSet<_>>();
+    let projected = engine.bdd.exists(engine.root, |v| !kept_variables.contains(&v));
+    if projected == NodeId::FALSE {
+        return Err(SliceError::VoidModel);
+    }
+    let tree = engine.compile_tree(&sliced);
+
+    // Every configuration which the tree allows, but the projection doesn't, is excluded by a clause.
+    // The clauses are shrunk as long as they still allow every projected configuration,
+    // so a single clause excludes as many configurations as possible.
+    let not_projected = engine.bdd.not(projected);
+    let mut missing = engine.bdd.and(tree, not_projected);
+    while missing != NodeId::FALSE {
+        let mut cube = path(&engine, missing);
+        let mut i = 0;
+        while i < cube.len() {
+            let smaller = [&cube[..i], &cube[i + 1..]].concat();
+            let restricted = smaller.iter().fold(projected, |node, &(variable, value)| engine.bdd.restrict(node, variable, value));
+            if restricted == NodeId::FALSE {
+                cube = smaller;
+            } else {
+                i += 1;
+            }
+        }
+
+        let cube_node = cube.iter().fold(NodeId::TRUE, |node, &(variable, value)| {
+            let variable = engine.bdd.variable(variable);
+            let literal = if value { variable } else { engine.bdd.not(variable) };
+            engine.bdd.and(node, literal)
+        });
+        let excluded = engine.bdd.not(cube_node);
+        missing = engine.bdd.and(missing, excluded);
+        sliced.cross_tree_constraints.push(clause(&engine, &cube));
+    }
+
+    simplify::drop_redundant_constraints(&mut sliced, &mut vec![]);
+    Ok(sliced)
+}
+
+/// Copies a feature of the slice, with the features of the slice below it.
+fn slice_feature(feature: &Feature, kept: &HashSet<&str>) -> Feature {
+    let mut groups = vec![];
+    let mut orphans = vec![];
+    for group in &feature.groups {
+        let (members, removed): (Vec<_>, Vec<_>) = group.features.iter()
+            .partition(|child| kept.contains(child.name.as_str()));
+
+        for child in removed.iter() {
+            orphans.extend(kept_descendants(child, kept));
+        }
+
+        if !members.is_empty() {
+            let max = group.max.min(members.len());
+            let min = group.min.saturating_sub(removed.len());
+            let members = members.into_iter().map(|child| slice_feature(child, kept)).collect();
+            groups.push(Group::new(members, min, max));
+        }
+    }
+
+    if !orphans.is_empty() {
+        groups.push(Group::optional(orphans));
+    }
+
+    let mut sliced = Feature::new(feature.name.clone(), groups, feature.is_abstract);
+    sliced.attributes = feature.attributes.clone();
+    sliced
+}
+
+/// The highest features of the slice below a feature outside the slice.
+fn kept_descendants(feature: &Feature, kept: &HashSet<&str>) -> Vec<Feature> {
+    feature.groups.iter()
+        .flat_map(|g| g.features.iter())
+        .flat_map(|child| if kept.contains(child.name.as_str()) {
+            vec![slice_feature(child, kept)]
+        } else {
+            kept_descendants(child, kept)
+        })
+        .collect()
+}
+
+/// Any path from f to the true terminal, as a selection or deselection of the variables on the path.
+fn path(engine: &Engine, mut f: NodeId) -> Vec<(u32, bool)> {
+    let mut path = vec![];
+    while let Some(variable) = engine.bdd.top_variable(f) {
+        let (low, high) = engine.bdd.children(f);
+        let value = low == NodeId::FALSE;
+        path.push((variable, value));
+        f = if value { high } else { low };
+    }
+    path
+}
+
+/// The constraint which excludes the cube, written as the selected features of the cube
+/// implying one of the deselected features.
+fn clause(engine: &Engine, cube: &[(u32, bool)]) -> CrossTreeConstraint {
+    let literals = |selected: bool| cube.iter()
+        .filter(|&&(_, value)| value == selected)
+        .map(|&(variable, _)| CrossTreeConstraint::Feature(engine.features()[variable as usize].clone()))
+        .collect::<Vec<_>>();
+    let selected = literals(true);
+    let deselected = literals(false);
+
+    match (single(selected, cross_tree_constraint::conjunction), single(deselected, cross_tree_constraint::disjunction)) {
+        (Some(selected), Some(deselected)) => cross_tree_constraint::implies(selected, deselected),
+        (Some(selected), None) => cross_tree_constraint::not(selected),
+        (None, Some(deselected)) => deselected,
+        (None, None) => unreachable!("Only a void model excludes the empty cube"),
+    }
+}
+
+/// Joins the operands with the operator, without wrapping a single operand, or None if there are no operands.
+fn single(mut operands: Vec<CrossTreeConstraint>, join: fn(Vec<CrossTreeConstraint>) -> CrossTreeConstraint) -> Option<CrossTreeConstraint> {
+    match operands.len() {
+        0 => None,
+        1 => operands.pop(),
+        _ => Some(join(operands)),
+    }
+}
@@ -2,6 +2,10 @@ use std::io::Write;
 
 use crate::{FeatureModel, cross_tree_constraint::CrossTreeConstraint, feature::Feature, group::Group, indent::tab};
 
+mod parse;
+
+pub use parse::{parse, ParseError, ParseErrorKind};
+
 pub fn write<W: Write>(writer: &mut W, feature_model: &FeatureModel) -> std::io::Result<()> {
     writeln!(writer, "features")?;
     write_feature(writer, &feature_model.root_feature, 1)?;
@@ -16,10 +20,16 @@ pub fn write<W: Write>(writer: &mut W, feature_model: &FeatureModel) -> std::io:
 
 fn write_feature<W: Write>(writer: &mut W, feature: &Feature, depth: usize) -> std::io::Result<()> {
     tab(writer, depth)?;
-    if feature.is_abstract {
-        writeln!(writer, "\"{}\" {{abstract}}", feature.name)?;
+    write!(writer, "\"{}\"", feature.name)?;
+
+    let attributes = feature.is_abstract.then(|| "abstract".to_owned())
+        .into_iter()
+        .chain(feature.attributes.iter().map(|(key, value)| format!("{} {value}", attribute_key(key))))
+        .collect::<Vec<_>>();
+    if attributes.is_empty() {
+        writeln!(writer)?;
     } else {
-        writeln!(writer, "\"{}\"", feature.name)?;
+        writeln!(writer, " {{{}}}", attributes.join(", "))?;
     }
 
     for group in &feature.groups {
@@ -29,9 +39,20 @@ fn write_feature<W: Write>(writer: &mut W, feature: &Feature, depth: usize) -> s
     Ok(())
 }
 
+/// Quotes the key of an attribute, unless it is a plain identifier.
+/// The keywords true and false are quoted too, since the parser reads them as booleans.
+fn attribute_key(key: &str) -> String {
+    let is_identifier = !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if is_identifier && !matches!(key, "true" | "false") {
+        key.to_owned()
+    } else {
+        format!("\"{key}\"")
+    }
+}
+
 fn write_group<W: Write>(writer: &mut W, group: &Group, depth: usize) -> std::io::Result<()> {
     tab(writer, depth)?;
-    write_group_cardinality(writer, group)?;
+    writeln!(writer, "{}", group.kind())?;
 
     for feature in &group.features {
         write_feature(writer, feature, depth + 1)?;
@@ -40,20 +61,6 @@ fn write_group<W: Write>(writer: &mut W, group: &Group, depth: usize) -> std::io
     Ok(())
 }
 
-fn write_group_cardinality<W: Write>(writer: &mut W, group: &Group) -> std::io::Result<()> {
-    let n = group.features.len();
-
-    match (group.min, group.max) {
-        (a, b) if a == b && b == n => writeln!(writer, "mandatory")?,
-        (0, m) if m == n => writeln!(writer, "optional")?,
-        (1, m) if m == n => writeln!(writer, "or")?,
-        (1, 1) => writeln!(writer, "alternative")?,
-        (a, b) => writeln!(writer, "[{a}..{b}]")?,
-    }
-
-    Ok(())
-} 
-
 fn write_constraints<W: Write>(writer: &mut W, constraints: &[CrossTreeConstraint]) -> std::io::Result<()> {
     for constraint in constraints {
         writeln!(writer, "\t{constraint}")?;
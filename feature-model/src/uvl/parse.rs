@@ -0,0 +1,675 @@
+use std::collections::BTreeMap;
+
+use crate::{FeatureModel, attribute::Attribute, cross_tree_constraint::{self, CrossTreeConstraint}, feature::Feature, group::Group};
+
+/// Error returned when a UVL document can't be parsed.
+/// Lines and columns are 1-indexed.
+#[derive(Debug, thiserror::Error)]
+#[error("Error at line {line}, column {column}: {kind}")]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ParseErrorKind {
+    #[error("Expected the keyword 'features'")]
+    MissingFeatures,
+    #[error("Expected a root feature")]
+    MissingRootFeature,
+    #[error("A feature model can only have one root feature")]
+    MultipleRootFeatures,
+    #[error("Unknown section '{0}'")]
+    UnknownSection(String),
+    #[error("Unexpected indentation")]
+    UnexpectedIndentation,
+    #[error("Expected a group type, found '{0}'")]
+    ExpectedGroup(String),
+    #[error("Invalid group cardinality '{0}'")]
+    InvalidCardinality(String),
+    #[error("Expected a feature name")]
+    ExpectedFeatureName,
+    #[error("Unterminated string")]
+    UnterminatedString,
+    #[error("Invalid value '{1}' of attribute '{0}'")]
+    InvalidAttributeValue(String, String),
+    #[error("Unexpected '{0}'")]
+    UnexpectedToken(String),
+    #[error("Unexpected end of line")]
+    UnexpectedEnd,
+}
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+/// Parses a feature model from UVL.
+///
+/// The parser supports the subset of UVL written by [crate::uvl::write]:
+/// a single root feature, groups with the keywords mandatory, optional, or, alternative
+/// and [a..b] cardinalities, attributes with boolean, integer, real and 'string' values or without a value,
+/// and constraints using !, &, |, => and <=> and the constants true and false.
+/// Indentation may use tabs or spaces, as long as it is consistent between siblings.
+pub fn parse(input: &str) -> Result<FeatureModel> {
+    let lines = input.lines()
+        .enumerate()
+        .filter_map(|(i, content)| Line::new(i + 1, content))
+        .collect::<Vec<_>>();
+
+    let mut parser = Parser { lines: &lines, position: 0 };
+    parser.parse_feature_model()
+}
+
+/// A non-empty line of UVL with its indentation stripped.
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    content: &'a str,
+}
+
+impl<'a> Line<'a> {
+    /// Creates a line, or returns None if the line is blank or a comment.
+    fn new(number: usize, raw: &'a str) -> Option<Line<'a>> {
+        let content = raw.trim_start();
+        let indent = raw.len() - content.len();
+        let content = content.trim_end();
+        if content.is_empty() || content.starts_with("//") {
+            None
+        } else {
+            Some(Line { number, indent, content })
+        }
+    }
+
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        self.error_at(0, kind)
+    }
+
+    /// Creates an error pointing at a byte offset into the content of the line.
+    fn error_at(&self, offset: usize, kind: ParseErrorKind) -> ParseError {
+        let column = self.indent + self.content[..offset].chars().count() + 1;
+        ParseError { line: self.number, column, kind }
+    }
+}
+
+struct Parser<'a, 'b> {
+    lines: &'b [Line<'a>],
+    position: usize,
+}
+
+impl<'a, 'b> Parser<'a, 'b> {
+    fn peek(&self) -> Option<&'b Line<'a>> {
+        self.lines.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&'b Line<'a>> {
+        let line = self.lines.get(self.position);
+        self.position += 1;
+        line
+    }
+
+    /// Returns the next line if it is indented further than the given indentation.
+    fn peek_child(&self, indent: usize) -> Option<&'b Line<'a>> {
+        self.peek().filter(|line| line.indent > indent)
+    }
+
+    fn parse_feature_model(&mut self) -> Result<FeatureModel> {
+        let header = self.next()
+            .ok_or(ParseError { line: 1, column: 1, kind: ParseErrorKind::MissingFeatures })?;
+        if header.content != "features" || header.indent != 0 {
+            return Err(header.error(ParseErrorKind::MissingFeatures));
+        }
+
+        let root_line = self.peek_child(0)
+            .ok_or_else(|| header.error(ParseErrorKind::MissingRootFeature))?;
+        let root_indent = root_line.indent;
+        let root_feature = self.parse_feature()?;
+        if let Some(line) = self.peek_child(0) {
+            let kind = if line.indent == root_indent {
+                ParseErrorKind::MultipleRootFeatures
+            } else {
+                ParseErrorKind::UnexpectedIndentation
+            };
+            return Err(line.error(kind));
+        }
+
+        let mut cross_tree_constraints = vec![];
+        if let Some(line) = self.next() {
+            if line.content != "constraints" {
+                let section = line.content.split_whitespace().next().unwrap_or_default();
+                return Err(line.error(ParseErrorKind::UnknownSection(section.to_owned())));
+            }
+
+            while let Some(line) = self.peek_child(0) {
+                cross_tree_constraints.push(parse_constraint(line)?);
+                self.position += 1;
+            }
+
+            if let Some(line) = self.peek() {
+                return Err(line.error(ParseErrorKind::UnknownSection(line.content.to_owned())));
+            }
+        }
+
+        Ok(FeatureModel::new(root_feature, cross_tree_constraints))
+    }
+
+    /// Parses the feature at the current line and all of its groups.
+    fn parse_feature(&mut self) -> Result<Feature> {
+        let line = self.next().expect("Caller ensures there is a line");
+        let indent = line.indent;
+        let (name, is_abstract, attributes) = parse_feature_line(line)?;
+
+        let mut groups = vec![];
+        let group_indent = self.peek_child(indent).map(|l| l.indent);
+        while let Some(line) = self.peek_child(indent) {
+            if Some(line.indent) != group_indent {
+                return Err(line.error(ParseErrorKind::UnexpectedIndentation));
+            }
+            groups.push(self.parse_group()?);
+        }
+
+        let mut feature = Feature::new(name, groups, is_abstract);
+        feature.attributes = attributes;
+        Ok(feature)
+    }
+
+    /// Parses the group at the current line and all of its features.
+    fn parse_group(&mut self) -> Result<Group> {
+        let line = self.next().expect("Caller ensures there is a line");
+        let indent = line.indent;
+        let cardinality = parse_group_line(line)?;
+
+        let mut features = vec![];
+        let feature_indent = self.peek_child(indent).map(|l| l.indent);
+        while let Some(line) = self.peek_child(indent) {
+            if Some(line.indent) != feature_indent {
+                return Err(line.error(ParseErrorKind::UnexpectedIndentation));
+            }
+            features.push(self.parse_feature()?);
+        }
+
+        let n = features.len();
+        let group = match cardinality {
+            Cardinality::Mandatory => Group::mandatory(features),
+            Cardinality::Optional => Group::optional(features),
+            Cardinality::Or => Group::new(features, 1, n),
+            Cardinality::Alternative => Group::new(features, 1, 1),
+            Cardinality::Range(min, max) => {
+                let max = max.unwrap_or(n);
+                if min > max || max > n {
+                    return Err(line.error(ParseErrorKind::InvalidCardinality(line.content.to_owned())));
+                }
+                Group::new(features, min, max)
+            }
+        };
+
+        Ok(group)
+    }
+}
+
+enum Cardinality {
+    Mandatory,
+    Optional,
+    Or,
+    Alternative,
+    Range(usize, Option<usize>),
+}
+
+/// Parses a line containing a feature name and an optional list of attributes.
+/// The abstract attribute marks the feature as abstract, and other attributes without a value are true.
+fn parse_feature_line(line: &Line) -> Result<(String, bool, BTreeMap<String, Attribute>)> {
+    let mut lexer = Lexer::new(line);
+    let name = match lexer.next_token()? {
+        Some((_, Token::Name(name))) => name,
+        Some((offset, _)) => return Err(line.error_at(offset, ParseErrorKind::ExpectedFeatureName)),
+        None => return Err(line.error(ParseErrorKind::ExpectedFeatureName)),
+    };
+
+    let mut is_abstract = false;
+    let mut attributes = BTreeMap::new();
+    if let Some((offset, token)) = lexer.next_token()? {
+        if token != Token::LeftBrace {
+            return Err(line.error_at(offset, ParseErrorKind::UnexpectedToken(token.to_string())));
+        }
+
+        loop {
+            let (key_offset, key) = match lexer.next_token()? {
+                Some((offset, Token::Name(key))) => (offset, key),
+                Some((offset, token)) => return Err(line.error_at(offset, ParseErrorKind::UnexpectedToken(token.to_string()))),
+                None => return Err(line.error_at(line.content.len(), ParseErrorKind::UnexpectedEnd)),
+            };
+
+            let (value, separator) = match lexer.next_token()? {
+                Some((offset, Token::Name(value))) => (Some(parse_attribute_value(line, offset, &key, value)?), lexer.next_token()?),
+                Some((_, Token::String(value))) => (Some(Attribute::String(value)), lexer.next_token()?),
+                Some((_, Token::Boolean(value))) => (Some(Attribute::Bool(value)), lexer.next_token()?),
+                separator => (None, separator),
+            };
+
+            match (key.as_str(), value) {
+                ("abstract", None | Some(Attribute::Bool(true))) => is_abstract = true,
+                ("abstract", Some(Attribute::Bool(false))) => is_abstract = false,
+                ("abstract", Some(value)) => return Err(line.error_at(key_offset, ParseErrorKind::InvalidAttributeValue(key, value.to_string()))),
+                (_, value) => {
+                    attributes.insert(key, value.unwrap_or(Attribute::Bool(true)));
+                }
+            }
+
+            match separator {
+                Some((_, Token::Comma)) => continue,
+                Some((_, Token::RightBrace)) => break,
+                Some((offset, token)) => return Err(line.error_at(offset, ParseErrorKind::UnexpectedToken(token.to_string()))),
+                None => return Err(line.error_at(line.content.len(), ParseErrorKind::UnexpectedEnd)),
+            }
+        }
+
+        if let Some((offset, token)) = lexer.next_token()? {
+            return Err(line.error_at(offset, ParseErrorKind::UnexpectedToken(token.to_string())));
+        }
+    }
+
+    Ok((name, is_abstract, attributes))
+}
+
+/// Parses an unquoted attribute value as an integer or a finite real, in that order.
+fn parse_attribute_value(line: &Line, offset: usize, key: &str, value: String) -> Result<Attribute> {
+    match (value.parse::<i64>(), value.parse::<f64>()) {
+        (Ok(integer), _) => Ok(Attribute::Integer(integer)),
+        (_, Ok(real)) if real.is_finite() => Ok(Attribute::Real(real)),
+        _ => Err(line.error_at(offset, ParseErrorKind::InvalidAttributeValue(key.to_owned(), value))),
+    }
+}
+
+/// Parses a line containing a group keyword or a cardinality of the form [n], [a..b] or [a..*].
+fn parse_group_line(line: &Line) -> Result<Cardinality> {
+    let cardinality = match line.content {
+        "mandatory" => Cardinality::Mandatory,
+        "optional" => Cardinality::Optional,
+        "or" => Cardinality::Or,
+        "alternative" => Cardinality::Alternative,
+        s if s.starts_with('[') => {
+            let invalid = || line.error(ParseErrorKind::InvalidCardinality(s.to_owned()));
+            let inner = s.strip_prefix('[')
+                .and_then(|s| s.strip_suffix(']'))
+                .ok_or_else(invalid)?;
+            match inner.split_once("..") {
+                Some((min, "*")) => Cardinality::Range(min.trim().parse().map_err(|_| invalid())?, None),
+                Some((min, max)) => {
+                    let min = min.trim().parse().map_err(|_| invalid())?;
+                    let max = max.trim().parse().map_err(|_| invalid())?;
+                    if min > max {
+                        return Err(invalid());
+                    }
+                    Cardinality::Range(min, Some(max))
+                }
+                None => {
+                    let n = inner.trim().parse().map_err(|_| invalid())?;
+                    Cardinality::Range(n, Some(n))
+                }
+            }
+        }
+        s => return Err(line.error(ParseErrorKind::ExpectedGroup(s.to_owned()))),
+    };
+
+    Ok(cardinality)
+}
+
+/// Parses a single cross-tree constraint spanning the whole line.
+fn parse_constraint(line: &Line) -> Result<CrossTreeConstraint> {
+    let mut parser = ConstraintParser {
+        line,
+        lexer: Lexer::new(line),
+        lookahead: None,
+    };
+
+    let constraint = parser.parse_equivalence()?;
+    if let Some((offset, token)) = parser.next_token()? {
+        return Err(line.error_at(offset, ParseErrorKind::UnexpectedToken(token.to_string())));
+    }
+
+    Ok(constraint)
+}
+
+/// Recursive descent parser for constraints.
+///
+/// The binding strength of the operators from weakest to strongest is <=>, =>, |, & and !.
+/// Binary operators are left associative.
+struct ConstraintParser<'a> {
+    line: &'a Line<'a>,
+    lexer: Lexer<'a>,
+    lookahead: Option<Option<(usize, Token)>>,
+}
+
+impl ConstraintParser<'_> {
+    fn next_token(&mut self) -> Result<Option<(usize, Token)>> {
+        match self.lookahead.take() {
+            Some(token) => Ok(token),
+            None => self.lexer.next_token(),
+        }
+    }
+
+    fn peek_token(&mut self) -> Result<Option<&Token>> {
+        if self.lookahead.is_none() {
+            self.lookahead = Some(self.lexer.next_token()?);
+        }
+
+        Ok(self.lookahead.as_ref().and_then(|t| t.as_ref()).map(|(_, t)| t))
+    }
+
+    /// Consumes the next token if it is equal to the given token.
+    fn accept(&mut self, token: Token) -> Result<bool> {
+        if self.peek_token()? == Some(&token) {
+            self.lookahead = None;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn parse_equivalence(&mut self) -> Result<CrossTreeConstraint> {
+        let mut left = self.parse_implication()?;
+        while self.accept(Token::Equivalence)? {
+            let right = self.parse_implication()?;
+            left = cross_tree_constraint::equivalent(left, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_implication(&mut self) -> Result<CrossTreeConstraint> {
+        let mut left = self.parse_disjunction()?;
+        while self.accept(Token::Implication)? {
+            let right = self.parse_disjunction()?;
+            left = cross_tree_constraint::implies(left, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_disjunction(&mut self) -> Result<CrossTreeConstraint> {
+        let mut left = self.parse_conjunction()?;
+        while self.accept(Token::Or)? {
+            let right = self.parse_conjunction()?;
+            left = cross_tree_constraint::or(left, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_conjunction(&mut self) -> Result<CrossTreeConstraint> {
+        let mut left = self.parse_unary()?;
+        while self.accept(Token::And)? {
+            let right = self.parse_unary()?;
+            left = cross_tree_constraint::and(left, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<CrossTreeConstraint> {
+        match self.next_token()? {
+            Some((_, Token::Not)) => Ok(cross_tree_constraint::not(self.parse_unary()?)),
+            Some((_, Token::Name(name))) => Ok(CrossTreeConstraint::Feature(name)),
+            Some((_, Token::Boolean(true))) => Ok(CrossTreeConstraint::And(Vec::new())),
+            Some((_, Token::Boolean(false))) => Ok(CrossTreeConstraint::Or(Vec::new())),
+            Some((_, Token::LeftParenthesis)) => {
+                let constraint = self.parse_equivalence()?;
+                match self.next_token()? {
+                    Some((_, Token::RightParenthesis)) => Ok(constraint),
+                    Some((offset, token)) => Err(self.line.error_at(offset, ParseErrorKind::UnexpectedToken(token.to_string()))),
+                    None => Err(self.line.error_at(self.line.content.len(), ParseErrorKind::UnexpectedEnd)),
+                }
+            }
+            Some((offset, token)) => Err(self.line.error_at(offset, ParseErrorKind::UnexpectedToken(token.to_string()))),
+            None => Err(self.line.error_at(self.line.content.len(), ParseErrorKind::UnexpectedEnd)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Name(String),
+    /// A single-quoted string, which is only used for attribute values.
+    /// A backslash escapes the next character, like a quote or another backslash.
+    String(String),
+    /// The unquoted keywords true and false. Quoted, they are names like any other.
+    Boolean(bool),
+    Not,
+    And,
+    Or,
+    Implication,
+    Equivalence,
+    LeftParenthesis,
+    RightParenthesis,
+    LeftBrace,
+    RightBrace,
+    Comma,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Name(name) => write!(f, "{name}"),
+            Token::String(value) => write!(f, "'{value}'"),
+            Token::Boolean(value) => write!(f, "{value}"),
+            Token::Not => write!(f, "!"),
+            Token::And => write!(f, "&"),
+            Token::Or => write!(f, "|"),
+            Token::Implication => write!(f, "=>"),
+            Token::Equivalence => write!(f, "<=>"),
+            Token::LeftParenthesis => write!(f, "("),
+            Token::RightParenthesis => write!(f, ")"),
+            Token::LeftBrace => write!(f, "{{"),
+            Token::RightBrace => write!(f, "}}"),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
+/// Splits the content of a line into tokens.
+/// Every token is returned with its byte offset into the line.
+struct Lexer<'a> {
+    line: &'a Line<'a>,
+    offset: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(line: &'a Line<'a>) -> Lexer<'a> {
+        Lexer { line, offset: 0 }
+    }
+
+    fn next_token(&mut self) -> Result<Option<(usize, Token)>> {
+        let content = self.line.content;
+        let rest = &content[self.offset..];
+        let trimmed = rest.trim_start();
+        let start = self.offset + rest.len() - trimmed.len();
+
+        let Some(c) = trimmed.chars().next() else {
+            self.offset = content.len();
+            return Ok(None);
+        };
+
+        let (token, length) = match c {
+            '!' => (Token::Not, 1),
+            '&' => (Token::And, 1),
+            '|' => (Token::Or, 1),
+            '(' => (Token::LeftParenthesis, 1),
+            ')' => (Token::RightParenthesis, 1),
+            '{' => (Token::LeftBrace, 1),
+            '}' => (Token::RightBrace, 1),
+            ',' => (Token::Comma, 1),
+            '=' if trimmed.starts_with("=>") => (Token::Implication, 2),
+            '<' if trimmed.starts_with("<=>") => (Token::Equivalence, 3),
+            '"' => {
+                let end = trimmed[1..].find('"')
+                    .ok_or_else(|| self.line.error_at(start, ParseErrorKind::UnterminatedString))?;
+                (Token::Name(trimmed[1..end + 1].to_owned()), end + 2)
+            }
+            '\'' => {
+                let mut value = String::new();
+                let mut characters = trimmed.char_indices().skip(1);
+                let end = loop {
+                    match characters.next() {
+                        Some((i, '\'')) => break i,
+                        Some((_, '\\')) => value.extend(characters.next().map(|(_, c)| c)),
+                        Some((_, c)) => value.push(c),
+                        None => return Err(self.line.error_at(start, ParseErrorKind::UnterminatedString)),
+                    }
+                };
+                (Token::String(value), end + 1)
+            }
+            c if is_identifier_char(c) => {
+                let end = trimmed.find(|c| !is_identifier_char(c))
+                    .unwrap_or(trimmed.len());
+                let token = match &trimmed[..end] {
+                    "true" => Token::Boolean(true),
+                    "false" => Token::Boolean(false),
+                    name => Token::Name(name.to_owned()),
+                };
+                (token, end)
+            }
+            c => return Err(self.line.error_at(start, ParseErrorKind::UnexpectedToken(c.to_string()))),
+        };
+
+        self.offset = start + length;
+        Ok(Some((start, token)))
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FeatureModel, cross_tree_constraint::{self, CrossTreeConstraint}, feature::Feature, group::Group, uvl};
+
+    use super::{Line, ParseError, ParseErrorKind, parse_constraint};
+
+    fn leaf(name: &str) -> Feature {
+        Feature::new_leaf(name.to_owned(), false)
+    }
+
+    fn write(feature_model: &FeatureModel) -> String {
+        let mut output = Vec::new();
+        uvl::write(&mut output, feature_model).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn write_parse_write_is_identical() {
+        let mut a = leaf("a");
+        a.attributes.insert("default".to_owned(), true.into());
+        a.attributes.insert("dependency".to_owned(), "it's C:\\dir".into());
+        let mut b = leaf("b");
+        b.attributes.insert("configurations".to_owned(), 3i64.into());
+        b.attributes.insert("frequency".to_owned(), 1.0.into());
+        b.attributes.insert("small".to_owned(), 2.5e-7.into());
+        b.attributes.insert("the key".to_owned(), false.into());
+        b.attributes.insert("true".to_owned(), 1e21.into());
+
+        let abstract_feature = Feature::new("abstract_0".to_owned(), vec![
+            Group::new(vec![leaf("x"), leaf("y")], 1, 2),
+            Group::new(vec![leaf("p"), leaf("q"), leaf("r")], 1, 1),
+        ], true);
+        let root_feature = Feature::new("crate".to_owned(), vec![
+            Group::mandatory(vec![leaf("core"), leaf("true")]),
+            Group::optional(vec![a, b]),
+            Group::new(vec![abstract_feature, leaf("z")], 1, 2),
+            Group::new(vec![leaf("s"), leaf("t"), leaf("u")], 2, 3),
+            Group::new(vec![leaf("v"), leaf("w"), leaf("o")], 1, 2),
+        ], false);
+
+        let constraints = vec![
+            cross_tree_constraint::implies(cross_tree_constraint::and("a", "b"), cross_tree_constraint::or("x", cross_tree_constraint::not("y"))),
+            cross_tree_constraint::not(cross_tree_constraint::or("a", "b")),
+            cross_tree_constraint::and(cross_tree_constraint::or("a", "b"), "z"),
+            cross_tree_constraint::implies("a", cross_tree_constraint::implies("b", "z")),
+            cross_tree_constraint::implies(cross_tree_constraint::implies("a", "b"), "z"),
+            cross_tree_constraint::equivalent("a", cross_tree_constraint::equivalent("b", "z")),
+            cross_tree_constraint::or("true", CrossTreeConstraint::And(vec![])),
+            cross_tree_constraint::implies(CrossTreeConstraint::Or(vec![]), cross_tree_constraint::not(CrossTreeConstraint::And(vec![]))),
+        ];
+        let feature_model = FeatureModel::new(root_feature, constraints);
+
+        let written = write(&feature_model);
+        assert!(written.contains("\"b\" {configurations 3, frequency 1.0, small 0.00000025, \"the key\" false, \"true\" 1000000000000000000000.0}"), "{written}");
+        let parsed = uvl::parse(&written).unwrap();
+        assert_eq!(write(&parsed), written);
+        assert_eq!(parsed.cross_tree_constraints, feature_model.cross_tree_constraints);
+    }
+
+    fn parse_error(input: &str) -> ParseError {
+        uvl::parse(input).err().expect("The input is invalid")
+    }
+
+    fn constraint(input: &str) -> CrossTreeConstraint {
+        parse_constraint(&Line::new(1, input).unwrap()).unwrap()
+    }
+
+    /// The cardinality of the single group of the root, parsed from the group line and three children.
+    fn cardinality(group: &str) -> Result<(usize, usize), ParseError> {
+        let input = format!("features\n\t\"r\"\n\t\t{group}\n\t\t\t\"a\"\n\t\t\t\"b\"\n\t\t\t\"c\"\n");
+        let feature_model = uvl::parse(&input)?;
+        let group = &feature_model.root_feature.groups[0];
+        Ok((group.min, group.max))
+    }
+
+    #[test]
+    fn cardinalities() {
+        assert_eq!(cardinality("mandatory").unwrap(), (3, 3));
+        assert_eq!(cardinality("optional").unwrap(), (0, 3));
+        assert_eq!(cardinality("or").unwrap(), (1, 3));
+        assert_eq!(cardinality("alternative").unwrap(), (1, 1));
+        assert_eq!(cardinality("[2]").unwrap(), (2, 2));
+        assert_eq!(cardinality("[1..2]").unwrap(), (1, 2));
+        assert_eq!(cardinality("[2..*]").unwrap(), (2, 3));
+        assert_eq!(cardinality("[ 0 .. 3 ]").unwrap(), (0, 3));
+
+        for invalid in ["[2..1]", "[1..4]", "[4]", "[5..*]", "[a..b]", "[1..2", "[]"] {
+            let error = cardinality(invalid).unwrap_err();
+            assert!(matches!(error.kind, ParseErrorKind::InvalidCardinality(_)), "{invalid}: {error}");
+        }
+        assert!(matches!(cardinality("some").unwrap_err().kind, ParseErrorKind::ExpectedGroup(_)));
+    }
+
+    #[test]
+    fn operator_precedence() {
+        let a = || CrossTreeConstraint::from("a");
+        let b = || CrossTreeConstraint::from("b");
+        let c = || CrossTreeConstraint::from("c");
+
+        assert_eq!(constraint("\"a\" <=> \"b\" => \"c\""), cross_tree_constraint::equivalent(a(), cross_tree_constraint::implies(b(), c())));
+        assert_eq!(constraint("\"a\" => \"b\" | \"c\""), cross_tree_constraint::implies(a(), cross_tree_constraint::or(b(), c())));
+        assert_eq!(constraint("\"a\" | \"b\" & \"c\""), cross_tree_constraint::or(a(), cross_tree_constraint::and(b(), c())));
+        assert_eq!(constraint("!\"a\" & \"b\""), cross_tree_constraint::and(cross_tree_constraint::not(a()), b()));
+        assert_eq!(constraint("\"a\" => \"b\" => \"c\""), cross_tree_constraint::implies(cross_tree_constraint::implies(a(), b()), c()));
+        assert_eq!(constraint("!(\"a\" | \"b\") & \"c\""), cross_tree_constraint::and(cross_tree_constraint::not(cross_tree_constraint::or(a(), b())), c()));
+        assert_eq!(constraint("\"true\" | true & false"), cross_tree_constraint::or("true", cross_tree_constraint::and(CrossTreeConstraint::And(vec![]), CrossTreeConstraint::Or(vec![]))));
+    }
+
+    #[test]
+    fn error_positions() {
+        let error = parse_error("feature\n\t\"r\"\n");
+        assert_eq!((error.line, error.column), (1, 1));
+        assert!(matches!(error.kind, ParseErrorKind::MissingFeatures));
+
+        let error = parse_error("features\n  \"r\"\n    optional\n        \"a\"\n      \"b\"\n");
+        assert_eq!((error.line, error.column), (5, 7));
+        assert!(matches!(error.kind, ParseErrorKind::UnexpectedIndentation));
+
+        let error = parse_error("features\n\t\"r\"\n\t\toptional\n\t\t\t\"a\n");
+        assert_eq!((error.line, error.column), (4, 4));
+        assert!(matches!(error.kind, ParseErrorKind::UnterminatedString));
+
+        let error = parse_error("features\n\t\"r\" {key NaN}\n");
+        assert_eq!((error.line, error.column), (2, 11));
+        assert!(matches!(error.kind, ParseErrorKind::InvalidAttributeValue(..)));
+
+        let error = parse_error("features\n    \"r\"\n\n// comment\nconstraints\n    \"r\" & (\"r\" | )\n");
+        assert_eq!((error.line, error.column), (6, 18));
+        assert!(matches!(error.kind, ParseErrorKind::UnexpectedToken(ref token) if token == ")"));
+
+        let error = parse_error("features\n\t\"r\"\nconstraints\n\t\"r\" &\n");
+        assert_eq!((error.line, error.column), (4, 7));
+        assert!(matches!(error.kind, ParseErrorKind::UnexpectedEnd));
+
+        let error = parse_error("features\n\t\"r\"\n\t\"s\"\n");
+        assert_eq!((error.line, error.column), (3, 2));
+        assert!(matches!(error.kind, ParseErrorKind::MultipleRootFeatures));
+    }
+}
@@ -1,10 +1,10 @@
 use std::{error::Error, fs::{self, File}, io::{stdin, BufWriter, Write}, path::{Path, PathBuf}};
 
 use cargo_toml::crate_id::{self, CrateId};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use concept::Concept;
 use configuration_scraper::configuration::Configuration;
-use feature_model::uvl;
+use feature_model::{json, render, simplify::{self, Pass}, uvl};
 use fm_synthesizer_fca::{concept, synthesizer, tree_constraints};
 use itertools::Itertools;
 use petgraph::{dot::Dot, graph::DiGraph};
@@ -22,6 +22,24 @@ struct Args {
     force: bool,
     #[arg(short, long, default_value = None)]
     ac_poset: Option<PathBuf>,
+    /// Writes a feature diagram of the model, as SVG if the path ends with .svg and as DOT otherwise.
+    #[arg(long, default_value = None)]
+    diagram: Option<PathBuf>,
+    /// Projects the model onto a comma-separated list of features, keeping the root feature.
+    #[arg(long, value_delimiter = ',')]
+    slice: Vec<String>,
+    /// Keeps the cross-tree constraints which are implied by the rest of the model.
+    #[arg(long, default_value_t = false)]
+    keep_redundant_constraints: bool,
+    /// The format of the feature model written to the destination.
+    #[arg(long, value_enum, default_value_t = Format::Uvl)]
+    format: Format,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    Uvl,
+    Json,
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -31,7 +49,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         return Err("User declined operation.".into());
     }
 
-    if let Some(path) = &args.ac_poset {
+    for path in [&args.ac_poset, &args.diagram].into_iter().flatten() {
         if !args.force && fs::exists(path)? && !confirm_overwrite(path) {
             return Err("User declined operation.".into());
         }
@@ -66,14 +84,30 @@ fn main() -> Result<(), Box<dyn Error>> {
     let uvl_file = File::create(args.destination)?;
     let mut writer = BufWriter::new(uvl_file);
     let tree_constraints = tree_constraints::max_depth::find(&ac_poset);
-    let feature_model = synthesizer::fm_from_ac_poset(&ac_poset, &features, &tree_constraints);
-    uvl::write(&mut writer, &feature_model)?;
+    let mut feature_model = synthesizer::fm_from_ac_poset(&ac_poset, &features, &tree_constraints);
+    if !args.keep_redundant_constraints {
+        let report = simplify::simplify(&mut feature_model, &[Pass::DropRedundantConstraints])?;
+        println!("Removed {} redundant cross-tree constraints", report.dropped_constraints.len());
+    }
+    if !args.slice.is_empty() {
+        feature_model = feature_model.slice(&args.slice.iter().map(String::as_str).collect::<Vec<_>>())?;
+    }
+    match args.format {
+        Format::Uvl => uvl::write(&mut writer, &feature_model)?,
+        Format::Json => json::write(&mut writer, &feature_model)?,
+    }
     writer.flush()?;
 
     if let Some(path) = &args.ac_poset {
         write_ac_poset(&ac_poset, path)?;
     }
 
+    if let Some(path) = &args.diagram {
+        let mut writer = BufWriter::new(File::create(path)?);
+        render::write(&mut writer, &feature_model, render::Format::from_path(path))?;
+        writer.flush()?;
+    }
+
     Ok(())
 }
 
@@ -1,10 +1,15 @@
 use std::iter::successors;
 
+use feature_model::count;
 use itertools::MultiUnzip;
+use num_bigint::BigUint;
 
 use crate::min_max::MinMaxExt;
 
 type Mask = u32;
+
+/// The base 10 logarithm of a number of configurations.
+/// Costs are kept in log space, so large feature models don't overflow to infinity.
 type Cost = f64;
 
 /// Finds the optimal set of groups which (locally) minimizes the number of configurations for a feature model.
@@ -12,22 +17,23 @@ type Cost = f64;
 /// The algorithm uses dynamic programming with a bit mask as the key.
 /// First, the cost of all possible groups are calculated, and stored in the cost vector.
 /// Once the costs are calculated, the dp table can be constructed and populated.
-/// The core of the algorithm is the recurrence: dp(S) = mininimize for all subsets G in S : dp(S \ G) * cost(G).
-pub fn find(n: usize, assignments: &[Mask], weight: impl Fn(usize) -> Cost) -> impl Iterator<Item = (Vec<usize>, usize, usize)> {
+/// The core of the algorithm is the recurrence: dp(S) = mininimize for all subsets G in S : dp(S \ G) * cost(G),
+/// which in log space becomes dp(S) = mininimize for all subsets G in S : dp(S \ G) + cost(G).
+pub fn find(n: usize, assignments: &[Mask], weight: impl Fn(usize) -> BigUint) -> impl Iterator<Item = (Vec<usize>, usize, usize)> {
     let full: Mask = 1 << n;
     let (cost, group_min, group_max): (Vec<Cost>, Vec<u32>, Vec<u32>) = (0..full)
         .map(|group| (group, group_cardinality(group, assignments)))
-        .map(|(group, (min, max))| (group_cost(mask_indices(group), group.count_ones() as usize, min, max, &weight), min, max))
+        .map(|(group, (min, max))| (group_cost(mask_indices(group), min, max, &weight), min, max))
         .multiunzip();
 
     let mut dp = vec![Cost::MAX; full as usize];
     let mut choice = vec![0; full as usize];
-    dp[0] = 1.0;
+    dp[0] = 0.0;
 
     for subset in 1..full {
         for group in enumerate_groups(subset) {
             let rest = (subset ^ group) as usize;
-            let val = dp[rest] * cost[group as usize];
+            let val = dp[rest] + cost[group as usize];
             if val < dp[subset as usize] {
                 dp[subset as usize] = val;
                 choice[subset as usize] = group;
@@ -66,19 +72,12 @@ fn group_cardinality(group: Mask, assignments: &[Mask]) -> (u32, u32) {
 
 /// Calcuates the cost of a specified group, its cardinality 
 /// and a weight function for every item in the group.
-pub fn group_cost(group: impl Iterator<Item = usize>, n: usize, min: u32, max: u32, weight: impl Fn(usize) -> f64) -> f64 {
-    let mut dp = vec![0.0; n + 1];
-    dp[0] = 1.0;
-
-    for i in group {
-        for k in (1..=n).rev() {
-            dp[k] += dp[k - 1] * weight(i);
-        }
-    }
-
-    (min..=max)
-        .map(|k| dp[k as usize])
-        .sum()
+/// 
+/// The cost is the base 10 logarithm of the group's number of configurations.
+pub fn group_cost(group: impl Iterator<Item = usize>, min: u32, max: u32, weight: impl Fn(usize) -> BigUint) -> Cost {
+    let weights = group.map(weight).collect::<Vec<_>>();
+    let config_count = count::cardinality_count(&weights, min as usize, max as usize);
+    count::log10(&config_count)
 }
 
 /// Returns an iterator of bitmasks representing suubsets of the given bitmask.
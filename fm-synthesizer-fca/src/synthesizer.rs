@@ -17,7 +17,8 @@ pub fn fm_from_ac_poset(ac_poset: &DiGraph<Concept, ()>, features: &[&str], tree
         abstract_feature_index: 1,
     };
     
-    let mut root_feature = synthesizer.construct_feature_diagram(maximal);
+    let root_feature = synthesizer.construct_feature_diagram(maximal);
+    let root_name = root_feature.name.clone();
     
     let used_features = ac_poset.node_weights()
         .flat_map(|concept| concept.features.iter())
@@ -52,6 +53,8 @@ pub fn fm_from_ac_poset(ac_poset: &DiGraph<Concept, ()>, features: &[&str], tree
         .chain(cross_tree_constraints_unused_features)
         .collect::<Vec<_>>();
 
+    let mut feature_model = FeatureModel::new(root_feature, cross_tree_constraints);
+
     if !unused_features.is_empty() {
         let unused_features_group = Group::optional(unused_features);
         let unused_features_abstract_feature = Feature::new(
@@ -60,10 +63,34 @@ pub fn fm_from_ac_poset(ac_poset: &DiGraph<Concept, ()>, features: &[&str], tree
             true,
         );
         let mandatory_group = Group::mandatory(vec![unused_features_abstract_feature]);
-        root_feature.groups.push(mandatory_group);
+        feature_model.add_group(&root_name, mandatory_group)
+            .expect("Unused features are not part of the feature diagram");
+    }
+
+    add_usage_attributes(&mut feature_model, ac_poset, maximal);
+    feature_model
+}
+
+/// Adds the number of configurations which enable each concrete feature as the `configurations` attribute,
+/// and the fraction of all configurations as the `frequency` attribute.
+fn add_usage_attributes(feature_model: &mut FeatureModel, ac_poset: &DiGraph<Concept, ()>, maximal: NodeIndex) {
+    let total = ac_poset[maximal].inherited_configurations.len();
+    let usage = ac_poset.node_weights()
+        .flat_map(|concept| concept.features.iter().map(|&f| (f, concept.inherited_configurations.len())))
+        .collect::<HashMap<_, _>>();
+
+    let features = feature_model.features()
+        .filter(|f| !f.is_abstract)
+        .map(|f| f.name.clone())
+        .collect::<Vec<_>>();
+    for feature in features {
+        let configurations = usage.get(feature.as_str()).copied().unwrap_or_default();
+        let frequency = if total == 0 { 0.0 } else { configurations as f64 / total as f64 };
+        feature_model.set_attribute(&feature, "configurations", configurations as i64)
+            .expect("The feature was found in the model");
+        feature_model.set_attribute(&feature, "frequency", frequency)
+            .expect("The feature was found in the model");
     }
-    
-    FeatureModel { root_feature, cross_tree_constraints }
 }
 
 struct Synthesizer<'a> {
@@ -100,7 +127,7 @@ impl<'a> Synthesizer<'a> {
             .collect::<Vec<_>>();
 
         let assignments = construct_assignment_masks(self.ac_poset, node, tree_neighbors);
-        let weight = |i: usize| features[i].config_count;
+        let weight = |i: usize| features[i].config_count().clone();
         let partition = optimal_groups::find(features.len(), &assignments, weight)
             .collect::<Vec<_>>();
 
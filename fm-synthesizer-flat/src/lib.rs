@@ -1,34 +1,76 @@
 use std::collections::{HashMap, HashSet};
 
-use cargo_toml::{feature_dependencies, toml_util};
+use cargo_toml::{feature_dependencies::{self, DependencyKind, Node}, manifest::{self, Manifest}};
 use feature_model::{FeatureModel, cross_tree_constraint::{self, CrossTreeConstraint}, feature::Feature, group::Group};
 use itertools::Itertools;
-use petgraph::{Direction, prelude::DiGraphMap};
+use petgraph::Direction;
 
-pub fn fm_from_cargo_toml(table: &toml::Table) -> Result<FeatureModel, toml_util::Error> {
-    let name = table
-        .get("package")
-        .and_then(|v| v.as_table())
-        .and_then(|t| t.get("name"))
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| toml_util::Error::KeyMissing("package.name is missing in toml".to_string()))?;
+pub fn fm_from_manifest(manifest: &Manifest) -> Result<FeatureModel, manifest::Error> {
+    let name = manifest.package()?.name.get_ref().as_str();
 
-    let mut feature_dependencies = feature_dependencies::from_cargo_toml(table)?;
+    let mut feature_dependencies = feature_dependencies::from_manifest(manifest);
+    let root = Node::Feature(name);
     
-    feature_dependencies.add_node(name);
+    feature_dependencies.add_node(root);
     for feature in feature_dependencies.nodes().collect::<Vec<_>>() {
-        if feature != name {
-            feature_dependencies.add_edge(feature, name, ());
+        if feature != root {
+            feature_dependencies.add_edge(feature, root, DependencyKind::Strong);
         }
     }
 
-    Ok(construct_feature_model(&feature_dependencies, name))
+    let mut feature_model = construct_feature_model(&feature_dependencies, root);
+    add_attributes(&mut feature_model, &feature_dependencies, manifest, root);
+    Ok(feature_model)
 }
 
-fn construct_feature_model<'a, E>(graph: &DiGraphMap<&'a str, E>, root: &'a str) -> FeatureModel {
+/// Adds the attributes known from the Cargo.toml to every feature except the root:
+/// `default` tells if the default feature enables the feature directly,
+/// and `dependency` names the package of an optional dependency, either behind an implicit feature or like 'dep:chrono',
+/// or the package whose feature is enabled by a feature of a dependency, like 'chrono/serde'.
+fn add_attributes(feature_model: &mut FeatureModel, graph: &feature_dependencies::Graph, manifest: &Manifest, root: Node) {
+    let default = manifest.features.get("default")
+        .into_iter()
+        .flatten()
+        .map(|s| feature_dependencies::referenced_node(s.get_ref()))
+        .filter(|&(_, kind)| kind == DependencyKind::Strong)
+        .map(|(node, _)| node)
+        .collect::<HashSet<_>>();
+
+    // A dependency may be listed in several tables, like dependencies and dev-dependencies, where only one is optional.
+    let dependencies = manifest.all_dependencies()
+        .map(|(name, dependency)| (name, (dependency.package_name(name), dependency.optional)))
+        .sorted_by_key(|&(_, (_, optional))| optional)
+        .collect::<HashMap<_, _>>();
+
+    for node in graph.nodes().filter(|&node| node != root) {
+        let feature = node.to_string();
+        feature_model.set_attribute(&feature, "default", default.contains(&node))
+            .expect("The feature was found in the model");
+
+        let package = match node {
+            // An explicit feature may have the name of an optional dependency which is referenced with 'dep:'.
+            Node::Feature(feature) if graph.contains_node(Node::Dependency(feature)) => None,
+            Node::Feature(dependency) | Node::Dependency(dependency) => dependencies.get(dependency)
+                .filter(|&&(_, optional)| optional)
+                .map(|&(package, _)| package),
+            Node::DependencyFeature(dependency, _) => Some(dependencies.get(dependency).map_or(dependency, |&(package, _)| package)),
+        };
+        if let Some(package) = package {
+            feature_model.set_attribute(&feature, "dependency", package)
+                .expect("The feature was found in the model");
+        }
+    }
+}
+
+/// Builds the tree from the strong edges, and turns the remaining strong edges and the weak edges into cross-tree constraints.
+fn construct_feature_model(graph: &feature_dependencies::Graph, root: Node) -> FeatureModel {
+    let strong_dependents = |node| graph.edges_directed(node, Direction::Incoming)
+        .filter(|&(_, _, &kind)| kind == DependencyKind::Strong)
+        .map(|(dependent, _, _)| dependent);
+
     let mut tree_edges = vec![];
     let mut cross_tree_edges = vec![];
-    let mut stack = graph.neighbors_directed(root, Direction::Incoming)
+    let mut stack = strong_dependents(root)
         .map(|neighbor| (neighbor, root))
         .collect::<Vec<_>>();
     let mut visited = HashSet::new();
@@ -36,7 +78,7 @@ fn construct_feature_model<'a, E>(graph: &DiGraphMap<&'a str, E>, root: &'a str)
     while let Some((feature, dependency)) = stack.pop() {
         if visited.insert(feature) {
             tree_edges.push((feature, dependency));
-            for neighbor in graph.neighbors_directed(feature, Direction::Incoming) {
+            for neighbor in strong_dependents(feature) {
                 stack.push((neighbor, feature));
             }
         } else {
@@ -51,19 +93,28 @@ fn construct_feature_model<'a, E>(graph: &DiGraphMap<&'a str, E>, root: &'a str)
 
     let root_feature = construct_feature_diagram(root, &mut tree);
 
+    let weak_edges = graph.all_edges()
+        .filter(|&(_, _, &kind)| kind == DependencyKind::Weak)
+        .map(|(feature, dependency_feature, _)| {
+            let dependency = feature_dependencies::dependency_node(graph, dependency_feature)
+                .expect("Only features of optional dependencies are referenced weakly");
+            weak_dependency_implication(feature, dependency, dependency_feature)
+        });
+
     let cross_tree_constraints = cross_tree_edges
         .into_iter()
         .into_group_map()
         .into_iter()
         .map(|(feature, dependencies)| feature_dependencies_implication(feature, &dependencies))
+        .chain(weak_edges)
         .collect::<Vec<_>>();
 
     FeatureModel::new(root_feature, cross_tree_constraints)
 }
 
-fn construct_feature_diagram(dependency: &str, tree: &mut HashMap<&str, Vec<&str>>) -> Feature {
+fn construct_feature_diagram<'a>(dependency: Node<'a>, tree: &mut HashMap<Node<'a>, Vec<Node<'a>>>) -> Feature {
     let group_features = tree
-        .remove(dependency)
+        .remove(&dependency)
         .unwrap_or_default()
         .into_iter()
         .map(|feature| construct_feature_diagram(feature, tree))
@@ -75,13 +126,19 @@ fn construct_feature_diagram(dependency: &str, tree: &mut HashMap<&str, Vec<&str
         vec![Group::optional(group_features)]
     };
 
-    Feature::new(dependency.to_owned(), groups, false)
+    Feature::new(dependency.to_string(), groups, false)
 }
 
-fn feature_dependencies_implication(feature: &str, dependencies: &[&str]) -> CrossTreeConstraint {
-    let mut dependency_constraint = CrossTreeConstraint::Feature(dependencies[0].to_owned());
-    for &dependency in &dependencies[1..] {
-        dependency_constraint = cross_tree_constraint::and(dependency_constraint, dependency);
-    }
-    cross_tree_constraint::implies(feature, dependency_constraint)
+fn feature_dependencies_implication(feature: Node, dependencies: &[Node]) -> CrossTreeConstraint {
+    let dependency_constraint = match dependencies {
+        [dependency] => CrossTreeConstraint::Feature(dependency.to_string()),
+        _ => cross_tree_constraint::conjunction(dependencies.iter().map(|d| d.to_string())),
+    };
+    cross_tree_constraint::implies(feature.to_string(), dependency_constraint)
+}
+
+/// The feature of the dependency is enabled when both the feature and the dependency are, like `(a & foo) => foo/bar`.
+fn weak_dependency_implication(feature: Node, dependency: Node, dependency_feature: Node) -> CrossTreeConstraint {
+    let condition = cross_tree_constraint::conjunction([feature.to_string(), dependency.to_string()]);
+    cross_tree_constraint::implies(condition, CrossTreeConstraint::Feature(dependency_feature.to_string()))
 }
\ No newline at end of file
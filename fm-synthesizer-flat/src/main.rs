@@ -1,8 +1,9 @@
-use std::{fs::File, io::BufWriter, path::PathBuf};
+use std::{fs::File, io::{BufWriter, Write}, path::PathBuf};
 
 use anyhow::{Context, bail};
-use clap::Parser;
-use feature_model::uvl;
+use cargo_toml::manifest::Manifest;
+use clap::{Parser, ValueEnum};
+use feature_model::{json, render, uvl};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -13,30 +14,60 @@ struct Args {
     name: Option<String>,
     #[arg(short, long, default_value = None)]
     path: Option<PathBuf>,
+    /// Writes a feature diagram of the model, as SVG if the path ends with .svg and as DOT otherwise.
+    #[arg(long, default_value = None)]
+    diagram: Option<PathBuf>,
+    /// Projects the model onto a comma-separated list of features, keeping the root feature.
+    #[arg(long, value_delimiter = ',')]
+    slice: Vec<String>,
+    /// The format of the feature model written to the destination.
+    #[arg(long, value_enum, default_value_t = Format::Uvl)]
+    format: Format,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Uvl,
+    Json,
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
-    let cargo_toml_content = if let Some(name) = args.name {
+    let manifest = if let Some(name) = args.name {
         let cargo_client = cargo_toml::default_cargo_client()?;
         let reqwest_client = cargo_toml::default_reqwest_client()?;
         let version = cargo_toml::latest_version(&name, &cargo_client)?;
         cargo_toml::download_cargo_toml(&reqwest_client, &name, &version.num)?
             .context("Crate does not contain a Cargo.toml file")?
+            .parse::<Manifest>()?
     } else if let Some(path) = args.path {
-        std::fs::read_to_string(path)?
+        // A manifest in a workspace may inherit its dependencies from the workspace root.
+        Manifest::read(&path)?
     } else {
         bail!("Either --name or --path needs to be specified");
     };
 
-    let table = cargo_toml_content.parse::<toml::Table>()?;
-    let feature_model = fm_synthesizer_flat::fm_from_cargo_toml(&table)?;
+    let mut feature_model = fm_synthesizer_flat::fm_from_manifest(&manifest)?;
+    if !args.slice.is_empty() {
+        feature_model = feature_model.slice(&args.slice.iter().map(String::as_str).collect::<Vec<_>>())?;
+    }
 
     let file = File::create(args.destination)?;
     let mut writer = BufWriter::new(file);
-    uvl::write(&mut writer, &feature_model)?;
+    match args.format {
+        Format::Uvl => uvl::write(&mut writer, &feature_model)?,
+        Format::Json => json::write(&mut writer, &feature_model)?,
+    }
+    writer.flush()?;
+
+    if let Some(path) = args.diagram {
+        let file = File::create(&path)?;
+        let mut writer = BufWriter::new(file);
+        render::write(&mut writer, &feature_model, render::Format::from_path(&path))?;
+        writer.flush()?;
+    }
 
     Ok(())
 }
\ No newline at end of file